@@ -0,0 +1,200 @@
+use std::{collections::HashMap, fmt, fs, io::ErrorKind};
+
+use crate::{
+    parse::ParseError,
+    utils::{Dataset, Part},
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Value {
+    Int(i64),
+    Text(String),
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Int(v) => write!(f, "{}", v),
+            Value::Text(v) => write!(f, "{}", v),
+        }
+    }
+}
+
+macro_rules! value_from_int {
+    ($($t:ty),*) => {
+        $(
+            impl From<$t> for Value {
+                fn from(v: $t) -> Self {
+                    Value::Int(i64::from(v))
+                }
+            }
+        )*
+    };
+}
+
+value_from_int!(u8, u16, u32, i8, i16, i32, i64);
+
+impl From<u64> for Value {
+    fn from(v: u64) -> Self {
+        Value::Int(i64::try_from(v).unwrap_or_else(|_| panic!("Answer {} overflows i64", v)))
+    }
+}
+
+impl From<usize> for Value {
+    fn from(v: usize) -> Self {
+        Value::Int(i64::try_from(v).unwrap_or_else(|_| panic!("Answer {} overflows i64", v)))
+    }
+}
+
+impl From<String> for Value {
+    fn from(v: String) -> Self {
+        Value::Text(v)
+    }
+}
+
+impl From<&str> for Value {
+    fn from(v: &str) -> Self {
+        Value::Text(v.to_string())
+    }
+}
+
+/// Answer returned by a puzzle: a single value for a per part run, a pair for a simult run
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Answer {
+    Single(Value),
+    Pair(Value, Value),
+}
+
+impl Answer {
+    pub fn single<V: Into<Value>>(v: V) -> Answer {
+        Answer::Single(v.into())
+    }
+
+    pub fn pair<V1: Into<Value>, V2: Into<Value>>(part1: V1, part2: V2) -> Answer {
+        Answer::Pair(part1.into(), part2.into())
+    }
+}
+
+/// Result of a puzzle, failing when its input can't be parsed
+pub type PuzzleResult = Result<Answer, ParseError>;
+
+impl fmt::Display for Answer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Answer::Single(v) => write!(f, "{}", v),
+            Answer::Pair(v1, v2) => write!(f, "({},{})", v1, v2),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    Ok,
+    Ko,
+    Unknown,
+    /// The run panicked before giving an answer
+    Failed,
+    /// The run gave no answer within the timeout
+    Timeout,
+}
+
+impl Status {
+    /// Merges the status of the two parts of a simult run, a failed run prevailing over a timed out
+    /// one, then KO over unknown
+    pub fn merge(self, other: Status) -> Status {
+        match (self, other) {
+            (Status::Failed, _) | (_, Status::Failed) => Status::Failed,
+            (Status::Timeout, _) | (_, Status::Timeout) => Status::Timeout,
+            (Status::Ko, _) | (_, Status::Ko) => Status::Ko,
+            (Status::Unknown, _) | (_, Status::Unknown) => Status::Unknown,
+            _ => Status::Ok,
+        }
+    }
+
+    pub fn is_failure(self) -> bool {
+        matches!(self, Status::Ko | Status::Failed | Status::Timeout)
+    }
+
+    /// Whether the run went up to an answer, so that its timings are meaningful
+    pub fn is_completed(self) -> bool {
+        !matches!(self, Status::Failed | Status::Timeout)
+    }
+}
+
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Status::Ok => write!(f, "OK"),
+            Status::Ko => write!(f, "KO"),
+            Status::Unknown => write!(f, "unknown"),
+            Status::Failed => write!(f, "FAILED"),
+            Status::Timeout => write!(f, "TIMEOUT"),
+        }
+    }
+}
+
+/// Expected answers of a day, read from the `day_N.answers` file stored next to the inputs.
+///
+/// Each non empty line is `part<1|2>.<dataset>=<answer>`, the dataset being `test`, `real` or `test_NAME`
+/// for a named test dataset, lines starting with `#` being comments.
+#[derive(Debug, Default)]
+pub struct ExpectedAnswers {
+    answers: HashMap<(Part, Dataset), Value>,
+}
+
+fn parse_key(key: &str) -> Option<(Part, Dataset)> {
+    let (part, dataset) = key.trim().split_once('.')?;
+    let part = match part {
+        "part1" => Part::Part1,
+        "part2" => Part::Part2,
+        _ => return None,
+    };
+    Some((part, Dataset::from_name(dataset)?))
+}
+
+impl ExpectedAnswers {
+    pub fn filename(data_dir: &str, day: &u8) -> String {
+        format!("{}/day_{}.answers", data_dir, day)
+    }
+
+    /// Loads the expected answers of the day, a missing file meaning that every answer is unknown
+    pub fn load(data_dir: &str, day: &u8) -> Result<ExpectedAnswers, String> {
+        let filename = ExpectedAnswers::filename(data_dir, day);
+        match fs::read_to_string(&filename) {
+            Ok(content) => ExpectedAnswers::parse(&content)
+                .map_err(|error| format!("Invalid answers file {}: {}", filename, error)),
+            Err(error) if error.kind() == ErrorKind::NotFound => Ok(ExpectedAnswers::default()),
+            Err(error) => Err(format!("Cannot read answers file {}: {}", filename, error)),
+        }
+    }
+
+    fn parse(content: &str) -> Result<ExpectedAnswers, String> {
+        let mut answers = HashMap::new();
+        for (pos, line) in content.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (key, value) = line
+                .split_once('=')
+                .and_then(|(key, value)| parse_key(key).map(|key| (key, value.trim())))
+                .ok_or_else(|| {
+                    format!(
+                        "line {} <{}> is not part<1|2>.<test|real|test_NAME>=<answer>",
+                        pos + 1,
+                        line
+                    )
+                })?;
+            let value = match value.parse::<i64>() {
+                Ok(v) => Value::Int(v),
+                Err(_) => Value::from(value),
+            };
+            answers.insert(key, value);
+        }
+        Ok(ExpectedAnswers { answers })
+    }
+
+    pub fn get(&self, part: Part, dataset: Dataset) -> Option<&Value> {
+        self.answers.get(&(part, dataset))
+    }
+}
@@ -0,0 +1,277 @@
+use std::{collections::HashMap, fmt, fs, time::Duration};
+
+use crate::{
+    report::{data_set_width, RunReport},
+    utils::{print_human, OutputFormat},
+};
+
+/// Statistics over the durations of several iterations, all values being in milliseconds
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Stats {
+    pub min: f64,
+    pub median: f64,
+    pub p95: f64,
+    pub max: f64,
+    pub mean: f64,
+    pub std_dev: f64,
+}
+
+fn percentile(sorted: &[f64], percent: f64) -> f64 {
+    let rank = ((percent / 100.0) * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+impl Stats {
+    pub fn from_durations(durations: &[Duration]) -> Stats {
+        if durations.is_empty() {
+            return Stats::default();
+        }
+        let mut sorted: Vec<f64> = durations.iter().map(|d| d.as_secs_f64() * 1000.0).collect();
+        sorted.sort_unstable_by(|a, b| a.total_cmp(b));
+        let nb = sorted.len() as f64;
+        let mean = sorted.iter().sum::<f64>() / nb;
+        let variance = sorted.iter().map(|v| (v - mean) * (v - mean)).sum::<f64>() / nb;
+        let median = if sorted.len().is_multiple_of(2) {
+            (sorted[sorted.len() / 2 - 1] + sorted[sorted.len() / 2]) / 2.0
+        } else {
+            sorted[sorted.len() / 2]
+        };
+        Stats {
+            min: sorted[0],
+            median,
+            p95: percentile(&sorted, 95.0),
+            max: sorted[sorted.len() - 1],
+            mean,
+            std_dev: variance.sqrt(),
+        }
+    }
+}
+
+impl fmt::Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "min {:.3} / median {:.3} / p95 {:.3} / max {:.3} / std dev {:.3} ms",
+            self.min, self.median, self.p95, self.max, self.std_dev
+        )
+    }
+}
+
+/// Timings of a run, the parse phase being the reading of the input and its preparation in the form
+/// consumed by the puzzle (lines, text, paragraphs), the solve phase being the puzzle call
+#[derive(Debug, Clone, Default)]
+pub struct Timings {
+    pub nb_warmup: u32,
+    pub nb_iterations: u32,
+    pub parse: Stats,
+    pub solve: Stats,
+}
+
+impl Timings {
+    pub fn new(
+        nb_warmup: u32,
+        parse_durations: &[Duration],
+        solve_durations: &[Duration],
+    ) -> Timings {
+        Timings {
+            nb_warmup,
+            nb_iterations: solve_durations.len() as u32,
+            parse: Stats::from_durations(parse_durations),
+            solve: Stats::from_durations(solve_durations),
+        }
+    }
+}
+
+pub const DEFAULT_REGRESSION_THRESHOLD: f64 = 10.0;
+
+/// Noise floor of the baseline comparison: smallest change of a median solve time, in
+/// milliseconds, that can be flagged as a regression or an improvement. Runs of a few microseconds
+/// are dominated by the timer resolution and scheduling, making smaller changes look like large
+/// percentages.
+pub const REGRESSION_NOISE_FLOOR_MS: f64 = 0.005;
+
+const BASELINE_HEADER: &str = "day,part,dataset,iterations,parse_median_ms,solve_min_ms,solve_median_ms,solve_p95_ms,solve_max_ms,solve_std_dev_ms";
+
+/// Key of a run in a baseline: day, part (`ALL` for a simult run) and dataset
+type BaselineKey = (u8, String, String);
+
+fn baseline_key(report: &RunReport) -> BaselineKey {
+    (
+        report.day,
+        report
+            .part
+            .map_or(String::from("ALL"), |p| format!("{:?}", p)),
+        report.data_set.to_string(),
+    )
+}
+
+/// Bench timings saved by a previous run, stored as a CSV file
+#[derive(Debug, Default)]
+pub struct Baseline {
+    entries: HashMap<BaselineKey, Timings>,
+}
+
+impl Baseline {
+    pub fn load(filename: &str) -> Result<Baseline, String> {
+        let content = fs::read_to_string(filename)
+            .map_err(|error| format!("Cannot read baseline {}: {}", filename, error))?;
+        let mut entries = HashMap::new();
+        for (pos, line) in content.lines().enumerate().skip(1) {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let invalid_line = || {
+                format!(
+                    "Invalid baseline {} at line {}: <{}>",
+                    filename,
+                    pos + 1,
+                    line
+                )
+            };
+            let fields: Vec<&str> = line.split(',').map(|f| f.trim()).collect();
+            if fields.len() != 10 {
+                return Err(invalid_line());
+            }
+            let values = fields[4..]
+                .iter()
+                .map(|f| f.parse::<f64>())
+                .collect::<Result<Vec<f64>, _>>()
+                .map_err(|_| invalid_line())?;
+            let key = (
+                fields[0].parse::<u8>().map_err(|_| invalid_line())?,
+                fields[1].to_string(),
+                fields[2].to_string(),
+            );
+            let timings = Timings {
+                nb_warmup: 0,
+                nb_iterations: fields[3].parse::<u32>().map_err(|_| invalid_line())?,
+                parse: Stats {
+                    median: values[0],
+                    ..Stats::default()
+                },
+                solve: Stats {
+                    min: values[1],
+                    median: values[2],
+                    p95: values[3],
+                    max: values[4],
+                    std_dev: values[5],
+                    mean: values[2],
+                },
+            };
+            entries.insert(key, timings);
+        }
+        Ok(Baseline { entries })
+    }
+
+    /// Saves the timings of the runs that gave an answer
+    pub fn save(filename: &str, reports: &[RunReport]) -> Result<(), String> {
+        let mut content = String::from(BASELINE_HEADER);
+        content.push('\n');
+        for report in reports.iter().filter(|r| r.status().is_completed()) {
+            let (day, part, data_set) = baseline_key(report);
+            let timings = &report.timings;
+            content.push_str(&format!(
+                "{},{},{},{},{:.6},{:.6},{:.6},{:.6},{:.6},{:.6}\n",
+                day,
+                part,
+                data_set,
+                timings.nb_iterations,
+                timings.parse.median,
+                timings.solve.min,
+                timings.solve.median,
+                timings.solve.p95,
+                timings.solve.max,
+                timings.solve.std_dev
+            ));
+        }
+        fs::write(filename, content)
+            .map_err(|error| format!("Cannot write baseline {}: {}", filename, error))
+    }
+
+    /// Prints the median solve time of each run against the baseline one, flagging as a regression
+    /// a median slower than the baseline by more than `threshold` percents, changes under
+    /// [`REGRESSION_NOISE_FLOOR_MS`] being ignored as noise, and gives the number of regressions
+    pub fn compare(&self, reports: &[RunReport], threshold: f64, output: OutputFormat) -> usize {
+        print_human(
+            output,
+            &format!(
+                "[ALL] Baseline comparison (solve median in ms, threshold {:.1}%)",
+                threshold
+            ),
+        );
+        let width = data_set_width(reports);
+        print_human(
+            output,
+            &format!(
+                "  Day | Part   | {:<width$} | Baseline   | Current    | Delta    | Status",
+                "Dataset",
+                width = width
+            ),
+        );
+        print_human(
+            output,
+            &format!(
+                "{:-<6}+{:-<8}+{:-<dataset$}+{:-<12}+{:-<12}+{:-<10}+{:-<12}",
+                "",
+                "",
+                "",
+                "",
+                "",
+                "",
+                "",
+                dataset = width + 2
+            ),
+        );
+        let mut nb_regressions = 0;
+        for report in reports.iter().filter(|r| r.status().is_completed()) {
+            let key = baseline_key(report);
+            let current = report.timings.solve.median;
+            let (baseline_str, delta_str, status) = match self.entries.get(&key) {
+                Some(baseline) => {
+                    let reference = baseline.solve.median;
+                    let delta = if reference > 0.0 {
+                        (current - reference) / reference * 100.0
+                    } else {
+                        0.0
+                    };
+                    let is_noise = (current - reference).abs() < REGRESSION_NOISE_FLOOR_MS;
+                    let status = if is_noise {
+                        "ok"
+                    } else if delta > threshold {
+                        nb_regressions += 1;
+                        "REGRESSION"
+                    } else if delta < -threshold {
+                        "improved"
+                    } else {
+                        "ok"
+                    };
+                    (
+                        format!("{:.3}", reference),
+                        format!("{:+.1}%", delta),
+                        status,
+                    )
+                }
+                None => (String::from("-"), String::from("-"), "new"),
+            };
+            print_human(
+                output,
+                &format!(
+                    "{:>5} | {:<6} | {:<width$} | {:>10} | {:>10.3} | {:>8} | {}",
+                    key.0,
+                    key.1,
+                    key.2,
+                    baseline_str,
+                    current,
+                    delta_str,
+                    status,
+                    width = width
+                ),
+            );
+        }
+        print_human(
+            output,
+            &format!("[ALL] {} regression(s) against baseline", nb_regressions),
+        );
+        nb_regressions
+    }
+}
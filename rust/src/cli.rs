@@ -0,0 +1,415 @@
+use std::time::Duration;
+
+use crate::{
+    bench::{DEFAULT_REGRESSION_THRESHOLD, REGRESSION_NOISE_FLOOR_MS},
+    utils::{
+        Dataset, DaysRestriction, LogLevel, OutputFormat, Part, RunOption, DEFAULT_BENCH_ITERATIONS,
+    },
+};
+
+pub fn usage() -> String {
+    format!(
+        "Usage: advent_of_code_2022 [OPTIONS] [DAYS...]
+
+Arguments:
+  [DAYS...]                    Days to run, as numbers or ranges (e.g. 1 11-16 20); all days by default
+
+Options:
+  -p, --part <1|2|both>        Part to run (default: both)
+  -s, --dataset <test|real|both|test_NAME>
+                               Dataset to run, test including the named test datasets (default: both)
+  -i, --input <FILE|->         Input of a single day and part replacing its datasets, - reading it from stdin
+  -l, --log-level <error|info|debug>
+                               Log level (default: info)
+  -d, --debug                  Shortcut for --log-level debug
+  -b, --bench [<N>]            Bench mode, timing N iterations of each puzzle (default: 10),
+                               a number following the option being taken as N rather than a day
+  -w, --warmup <N>             Untimed iterations run before the timed ones, requiring --bench (default: 1)
+  -j, --jobs <N>               Number of runs executed in parallel (default: 1)
+  -t, --timeout <SECONDS>      Time given to each run to answer before it is reported as timed out (default: none)
+      --baseline <FILE>        Compare bench timings against a baseline saved by --save-baseline
+      --save-baseline <FILE>   Save bench timings as a CSV baseline
+      --regression-threshold <PCT>
+                               Median slowdown flagged as a regression against the baseline (default: {threshold}),
+                               changes under {noise_floor} µs being ignored as noise,
+                               any regression making the run exit with status 1
+      --format <text|json>     Output format, json printing one JSON record per day, part and dataset (default: text)
+      --data-dir <DIR>         Directory containing the day_N.txt inputs
+                               (default: $AOC_DATA_DIR, else ./data, else the data directory of the sources),
+                               a missing input of the default directory being read from the day_N.dat of the shared one
+      --list                   List the available days and their run style
+  -h, --help                   Print this help",
+        threshold = DEFAULT_REGRESSION_THRESHOLD,
+        noise_floor = REGRESSION_NOISE_FLOOR_MS * 1000.0,
+    )
+}
+
+pub enum Command {
+    Run(Box<CliOptions>),
+    List,
+    Help,
+}
+
+#[derive(Debug, Default)]
+pub struct CliOptions {
+    days: Option<Vec<u8>>,
+    part: Option<Part>,
+    dataset: Option<Dataset>,
+    input: Option<String>,
+    log_level: Option<LogLevel>,
+    bench_iterations: Option<u32>,
+    bench_warmup: Option<u32>,
+    baseline: Option<String>,
+    save_baseline: Option<String>,
+    regression_threshold: Option<f64>,
+    data_dir: Option<String>,
+    format: Option<OutputFormat>,
+    nb_jobs: Option<usize>,
+    timeout: Option<Duration>,
+}
+
+impl CliOptions {
+    pub fn days_restriction(&self) -> DaysRestriction<'_> {
+        &self.days
+    }
+
+    pub fn baseline(&self) -> Option<&str> {
+        self.baseline.as_deref()
+    }
+
+    pub fn save_baseline(&self) -> Option<&str> {
+        self.save_baseline.as_deref()
+    }
+
+    pub fn regression_threshold(&self) -> f64 {
+        self.regression_threshold
+            .unwrap_or(DEFAULT_REGRESSION_THRESHOLD)
+    }
+
+    pub fn run_option(&self) -> RunOption<'_> {
+        let mut options = RunOption::default(self.days_restriction());
+        if let Some(part) = self.part {
+            options = options.with_part(part);
+        }
+        if let Some(dataset) = self.dataset {
+            options = options.with_dataset(dataset);
+        }
+        if let Some(log_level) = self.log_level {
+            options = options.with_log_level(log_level);
+        }
+        if let Some(nb_iterations) = self.bench_iterations {
+            options = options.bench().with_iterations(nb_iterations);
+        }
+        if let Some(nb_warmup) = self.bench_warmup {
+            options = options.with_warmup(nb_warmup);
+        }
+        if let Some(format) = self.format {
+            options = options.with_format(format);
+        }
+        if let Some(nb_jobs) = self.nb_jobs {
+            options = options.with_jobs(nb_jobs);
+        }
+        if let Some(timeout) = self.timeout {
+            options = options.with_timeout(timeout);
+        }
+        if let Some(input) = &self.input {
+            options = options.with_input(input);
+        }
+        if let Some(data_dir) = &self.data_dir {
+            options = options.with_data_dir(data_dir);
+        }
+        options
+    }
+}
+
+fn parse_days(value: &str, days: &mut Vec<u8>) -> Result<(), String> {
+    let parse_day = |day: &str| {
+        day.trim()
+            .parse::<u8>()
+            .ok()
+            .filter(|d| (1..=25).contains(d))
+            .ok_or_else(|| format!("Invalid day <{}>, expecting a number between 1 and 25", day))
+    };
+    for item in value.split(',').filter(|item| !item.is_empty()) {
+        match item.split_once('-') {
+            Some((from, to)) => {
+                let (from, to) = (parse_day(from)?, parse_day(to)?);
+                if from > to {
+                    return Err(format!("Invalid day range <{}>", item));
+                }
+                days.extend(from..=to);
+            }
+            None => days.push(parse_day(item)?),
+        }
+    }
+    Ok(())
+}
+
+fn parse_part(value: &str) -> Result<Option<Part>, String> {
+    match value {
+        "1" => Ok(Some(Part::Part1)),
+        "2" => Ok(Some(Part::Part2)),
+        "both" => Ok(None),
+        _ => Err(format!("Invalid part <{}>, expecting 1, 2 or both", value)),
+    }
+}
+
+fn parse_dataset(value: &str) -> Result<Option<Dataset>, String> {
+    match value {
+        "both" => Ok(None),
+        _ => match Dataset::from_name(value) {
+            Some(Dataset::Input) | None => Err(format!(
+                "Invalid dataset <{}>, expecting test, real, both or test_NAME",
+                value
+            )),
+            data_set => Ok(data_set),
+        },
+    }
+}
+
+fn parse_log_level(value: &str) -> Result<LogLevel, String> {
+    match value {
+        "error" => Ok(LogLevel::ERROR),
+        "info" => Ok(LogLevel::INFO),
+        "debug" => Ok(LogLevel::DEBUG),
+        _ => Err(format!(
+            "Invalid log level <{}>, expecting error, info or debug",
+            value
+        )),
+    }
+}
+
+fn parse_format(value: &str) -> Result<OutputFormat, String> {
+    match value {
+        "text" => Ok(OutputFormat::Text),
+        "json" => Ok(OutputFormat::JsonLines),
+        _ => Err(format!(
+            "Invalid format <{}>, expecting text or json",
+            value
+        )),
+    }
+}
+
+fn parse_iterations(value: &str) -> Result<u32, String> {
+    value
+        .parse::<u32>()
+        .ok()
+        .filter(|nb| *nb > 0)
+        .ok_or_else(|| format!("Invalid iteration count <{}>", value))
+}
+
+pub fn parse_args<I: Iterator<Item = String>>(args: I) -> Result<Command, String> {
+    let mut args = args.peekable();
+    let mut options = CliOptions::default();
+    let mut days: Vec<u8> = vec![];
+
+    while let Some(arg) = args.next() {
+        let (name, inline_value) = match arg.split_once('=') {
+            Some((name, value)) if name.starts_with('-') => {
+                (name.to_string(), Some(value.to_string()))
+            }
+            _ => (arg.clone(), None),
+        };
+        let mut value = |name: &str| {
+            inline_value
+                .clone()
+                .or_else(|| args.next())
+                .ok_or_else(|| format!("Missing value for {}", name))
+        };
+        match name.as_str() {
+            "-h" | "--help" => return Ok(Command::Help),
+            "--list" => return Ok(Command::List),
+            "-p" | "--part" => options.part = parse_part(&value(&name)?)?,
+            "-s" | "--dataset" => options.dataset = parse_dataset(&value(&name)?)?,
+            "-i" | "--input" => options.input = Some(value(&name)?),
+            "-l" | "--log-level" => options.log_level = Some(parse_log_level(&value(&name)?)?),
+            "-d" | "--debug" => options.log_level = Some(LogLevel::DEBUG),
+            "-b" | "--bench" => {
+                let nb = inline_value.or_else(|| args.next_if(|next| next.parse::<u32>().is_ok()));
+                options.bench_iterations = Some(match nb {
+                    Some(nb) => parse_iterations(&nb)?,
+                    None => DEFAULT_BENCH_ITERATIONS,
+                })
+            }
+            "-w" | "--warmup" => {
+                let value = value(&name)?;
+                options.bench_warmup = Some(
+                    value
+                        .parse::<u32>()
+                        .map_err(|_| format!("Invalid warmup count <{}>", value))?,
+                )
+            }
+            "-j" | "--jobs" => {
+                let value = value(&name)?;
+                options.nb_jobs = Some(
+                    value
+                        .parse::<usize>()
+                        .ok()
+                        .filter(|nb| *nb > 0)
+                        .ok_or_else(|| format!("Invalid job count <{}>", value))?,
+                )
+            }
+            "-t" | "--timeout" => {
+                let value = value(&name)?;
+                options.timeout = Some(
+                    value
+                        .parse::<f64>()
+                        .ok()
+                        .filter(|t| t.is_finite() && *t > 0.0)
+                        .map(Duration::from_secs_f64)
+                        .ok_or_else(|| {
+                            format!("Invalid timeout <{}>, expecting a number of seconds", value)
+                        })?,
+                )
+            }
+            "--baseline" => options.baseline = Some(value(&name)?),
+            "--save-baseline" => options.save_baseline = Some(value(&name)?),
+            "--regression-threshold" => {
+                let value = value(&name)?;
+                options.regression_threshold = Some(
+                    value
+                        .parse::<f64>()
+                        .ok()
+                        .filter(|t| *t >= 0.0)
+                        .ok_or_else(|| format!("Invalid regression threshold <{}>", value))?,
+                )
+            }
+            "--format" => options.format = Some(parse_format(&value(&name)?)?),
+            "--data-dir" => options.data_dir = Some(value(&name)?),
+            _ if name.starts_with('-') => return Err(format!("Unknown option {}", name)),
+            _ => parse_days(&arg, &mut days)?,
+        }
+    }
+
+    if options.bench_iterations.is_none()
+        && (options.baseline.is_some() || options.save_baseline.is_some())
+    {
+        return Err(String::from(
+            "--baseline and --save-baseline require --bench",
+        ));
+    }
+    if options.bench_iterations.is_none() && options.bench_warmup.is_some() {
+        return Err(String::from("--warmup requires --bench"));
+    }
+    if options.input.is_some() {
+        if options.dataset.is_some() {
+            return Err(String::from("--input can't be combined with --dataset"));
+        }
+        if days.len() != 1 {
+            return Err(String::from("--input requires a single day"));
+        }
+        if options.part.is_none() {
+            return Err(String::from("--input requires a single part"));
+        }
+    }
+    if !days.is_empty() {
+        days.sort_unstable();
+        days.dedup();
+        options.days = Some(days);
+    }
+    Ok(Command::Run(Box::new(options)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &str) -> Result<Command, String> {
+        parse_args(args.split_whitespace().map(String::from))
+    }
+
+    fn options(args: &str) -> CliOptions {
+        match parse(args) {
+            Ok(Command::Run(options)) => *options,
+            Ok(_) => panic!("no run for <{}>", args),
+            Err(err) => panic!("error for <{}>: {}", args, err),
+        }
+    }
+
+    fn error(args: &str) -> String {
+        match parse(args) {
+            Err(err) => err,
+            Ok(_) => panic!("no error for <{}>", args),
+        }
+    }
+
+    #[test]
+    fn parses_days_and_ranges() {
+        assert_eq!(options("").days, None);
+        assert_eq!(options("12 1-3,2 20").days, Some(vec![1, 2, 3, 12, 20]));
+        assert!(error("26").starts_with("Invalid day <26>"));
+        assert_eq!(error("5-3"), "Invalid day range <5-3>");
+    }
+
+    #[test]
+    fn takes_option_values_inline_or_from_the_next_argument() {
+        let options = options("-p 2 --dataset=real -l=debug --jobs 3 -t 1.5 --format json");
+        assert_eq!(options.part, Some(Part::Part2));
+        assert_eq!(options.dataset, Some(Dataset::Real));
+        assert_eq!(options.nb_jobs, Some(3));
+        assert_eq!(options.timeout, Some(Duration::from_millis(1500)));
+        assert_eq!(options.format, Some(OutputFormat::JsonLines));
+        assert_eq!(error("-p"), "Missing value for -p");
+        assert_eq!(error("--bogus"), "Unknown option --bogus");
+    }
+
+    #[test]
+    fn takes_bench_iterations_inline_or_as_the_next_number() {
+        let inline = options("-b=20 7");
+        assert_eq!(inline.bench_iterations, Some(20));
+        assert_eq!(inline.days, Some(vec![7]));
+        let separate = options("-b 20 7");
+        assert_eq!(separate.bench_iterations, Some(20));
+        assert_eq!(separate.days, Some(vec![7]));
+        let default = options("--bench 1-2");
+        assert_eq!(default.bench_iterations, Some(DEFAULT_BENCH_ITERATIONS));
+        assert_eq!(default.days, Some(vec![1, 2]));
+        assert_eq!(
+            options("-b").bench_iterations,
+            Some(DEFAULT_BENCH_ITERATIONS)
+        );
+        assert_eq!(error("-b 0"), "Invalid iteration count <0>");
+    }
+
+    #[test]
+    fn requires_bench_for_warmup_and_baselines() {
+        assert_eq!(options("-b -w 3").bench_warmup, Some(3));
+        assert_eq!(error("-w 3"), "--warmup requires --bench");
+        assert_eq!(
+            error("--baseline base.csv"),
+            "--baseline and --save-baseline require --bench"
+        );
+        assert_eq!(
+            options("-b --save-baseline base.csv").save_baseline(),
+            Some("base.csv")
+        );
+    }
+
+    #[test]
+    fn requires_a_single_day_and_part_for_an_explicit_input() {
+        let options = options("-i - -p 1 7");
+        assert_eq!(options.input.as_deref(), Some("-"));
+        assert_eq!(options.days, Some(vec![7]));
+        assert_eq!(error("-i - -p 1"), "--input requires a single day");
+        assert_eq!(error("-i - -p 1 7 8"), "--input requires a single day");
+        assert_eq!(error("-i - 7"), "--input requires a single part");
+        assert_eq!(error("-i - -p both 7"), "--input requires a single part");
+        assert_eq!(
+            error("-i - -p 1 -s test 7"),
+            "--input can't be combined with --dataset"
+        );
+    }
+
+    #[test]
+    fn renders_the_comparison_defaults_in_the_usage() {
+        let usage = usage();
+        assert!(usage.contains("against the baseline (default: 10),"));
+        assert!(usage.contains("changes under 5 µs being ignored as noise"));
+    }
+
+    #[test]
+    fn stops_at_help_and_list() {
+        assert!(matches!(parse("7 --help -p 9"), Ok(Command::Help)));
+        assert!(matches!(parse("--list"), Ok(Command::List)));
+    }
+}
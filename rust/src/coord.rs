@@ -0,0 +1,161 @@
+use std::ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign};
+
+/// 2D coordinate or vector, `x` growing to the right and `y` growing down as in the inputs
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Coord {
+    pub x: i32,
+    pub y: i32,
+}
+
+impl Coord {
+    pub const ZERO: Coord = Coord::new(0, 0);
+
+    pub const fn new(x: i32, y: i32) -> Coord {
+        Coord { x, y }
+    }
+
+    pub fn manhattan(self, other: Coord) -> u32 {
+        self.x.abs_diff(other.x) + self.y.abs_diff(other.y)
+    }
+
+    /// Quarter turn clockwise around the origin, as seen on the screen
+    #[allow(dead_code)]
+    pub fn rotate_right(self) -> Coord {
+        Coord::new(-self.y, self.x)
+    }
+
+    /// Quarter turn counterclockwise around the origin, as seen on the screen
+    #[allow(dead_code)]
+    pub fn rotate_left(self) -> Coord {
+        Coord::new(self.y, -self.x)
+    }
+
+    pub fn neighbours4(self) -> impl Iterator<Item = Coord> {
+        Direction::CARDINALS
+            .iter()
+            .map(move |direction| self + direction.offset())
+    }
+
+    pub fn neighbours8(self) -> impl Iterator<Item = Coord> {
+        Direction::ALL
+            .iter()
+            .map(move |direction| self + direction.offset())
+    }
+}
+
+impl Add for Coord {
+    type Output = Coord;
+
+    fn add(self, other: Coord) -> Coord {
+        Coord::new(self.x + other.x, self.y + other.y)
+    }
+}
+
+impl AddAssign for Coord {
+    fn add_assign(&mut self, other: Coord) {
+        *self = *self + other;
+    }
+}
+
+impl Sub for Coord {
+    type Output = Coord;
+
+    fn sub(self, other: Coord) -> Coord {
+        Coord::new(self.x - other.x, self.y - other.y)
+    }
+}
+
+impl SubAssign for Coord {
+    fn sub_assign(&mut self, other: Coord) {
+        *self = *self - other;
+    }
+}
+
+impl Neg for Coord {
+    type Output = Coord;
+
+    fn neg(self) -> Coord {
+        Coord::new(-self.x, -self.y)
+    }
+}
+
+impl Mul<i32> for Coord {
+    type Output = Coord;
+
+    fn mul(self, factor: i32) -> Coord {
+        Coord::new(self.x * factor, self.y * factor)
+    }
+}
+
+/// Cardinal and diagonal directions, in clockwise order from north
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Direction {
+    North,
+    NorthEast,
+    East,
+    SouthEast,
+    South,
+    SouthWest,
+    West,
+    NorthWest,
+}
+
+impl Direction {
+    pub const CARDINALS: [Direction; 4] = [
+        Direction::North,
+        Direction::East,
+        Direction::South,
+        Direction::West,
+    ];
+
+    pub const ALL: [Direction; 8] = [
+        Direction::North,
+        Direction::NorthEast,
+        Direction::East,
+        Direction::SouthEast,
+        Direction::South,
+        Direction::SouthWest,
+        Direction::West,
+        Direction::NorthWest,
+    ];
+
+    /// Move of one step in the direction, north going up
+    pub fn offset(self) -> Coord {
+        match self {
+            Direction::North => Coord::new(0, -1),
+            Direction::NorthEast => Coord::new(1, -1),
+            Direction::East => Coord::new(1, 0),
+            Direction::SouthEast => Coord::new(1, 1),
+            Direction::South => Coord::new(0, 1),
+            Direction::SouthWest => Coord::new(-1, 1),
+            Direction::West => Coord::new(-1, 0),
+            Direction::NorthWest => Coord::new(-1, -1),
+        }
+    }
+
+    /// Direction after turning clockwise by `eighths` eighths of a full turn, negative turning
+    /// counterclockwise
+    pub fn turn(self, eighths: i32) -> Direction {
+        Direction::ALL[(self as i32 + eighths).rem_euclid(8) as usize]
+    }
+
+    #[allow(dead_code)]
+    pub fn turn_right(self) -> Direction {
+        self.turn(2)
+    }
+
+    #[allow(dead_code)]
+    pub fn turn_left(self) -> Direction {
+        self.turn(-2)
+    }
+
+    #[allow(dead_code)]
+    pub fn opposite(self) -> Direction {
+        self.turn(4)
+    }
+
+    #[allow(dead_code)]
+    pub fn is_cardinal(self) -> bool {
+        (self as usize).is_multiple_of(2)
+    }
+}
@@ -0,0 +1,127 @@
+use std::hash::Hash;
+
+use rustc_hash::FxHashMap;
+
+/// Repetition of a simulation state, found when a fingerprint is seen a second time
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cycle<V> {
+    /// Step at which the state was first seen
+    pub start: usize,
+    /// Number of steps before the state repeats
+    pub length: usize,
+    /// Value recorded with the state when first seen
+    pub start_value: V,
+}
+
+impl<V> Cycle<V> {
+    /// Number of whole cycles fitting between `current` and `target`, and the steps left after
+    /// skipping them
+    pub fn skip(&self, current: usize, target: usize) -> (usize, usize) {
+        let remaining = target.saturating_sub(current);
+        (remaining / self.length, remaining % self.length)
+    }
+}
+
+/// Records state fingerprints step by step until one comes back
+#[derive(Debug)]
+pub struct CycleDetector<K, V> {
+    seen: FxHashMap<K, (usize, V)>,
+}
+
+impl<K: Hash + Eq, V: Clone> CycleDetector<K, V> {
+    pub fn new() -> CycleDetector<K, V> {
+        CycleDetector {
+            seen: FxHashMap::default(),
+        }
+    }
+
+    /// Records the fingerprint of the state reached at `step` with a value to extrapolate, giving
+    /// the cycle if the fingerprint was already recorded at an earlier step
+    pub fn record(&mut self, step: usize, key: K, value: V) -> Option<Cycle<V>> {
+        match self.seen.get(&key) {
+            Some((start, start_value)) => Some(Cycle {
+                start: *start,
+                length: step - start,
+                start_value: start_value.clone(),
+            }),
+            None => {
+                self.seen.insert(key, (step, value));
+                None
+            }
+        }
+    }
+}
+
+impl<K: Hash + Eq, V: Clone> Default for CycleDetector<K, V> {
+    fn default() -> Self {
+        CycleDetector::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PREFIX: usize = 3;
+    const PERIOD: usize = 4;
+
+    /// State reached at the step, distinct during the prefix then repeating with the period
+    fn state(step: usize) -> usize {
+        if step < PREFIX {
+            100 + step
+        } else {
+            (step - PREFIX) % PERIOD
+        }
+    }
+
+    /// Runs the sequence until a cycle is found, giving it with the step it was found at
+    fn detect() -> (Cycle<usize>, usize) {
+        let mut detector = CycleDetector::new();
+        (0..)
+            .find_map(|step| {
+                detector
+                    .record(step, state(step), 2 * step)
+                    .map(|cycle| (cycle, step))
+            })
+            .unwrap()
+    }
+
+    #[test]
+    fn detects_the_cycle_after_the_prefix() {
+        let (cycle, step) = detect();
+        assert_eq!(step, PREFIX + PERIOD);
+        assert_eq!(
+            cycle,
+            Cycle {
+                start: PREFIX,
+                length: PERIOD,
+                start_value: 2 * PREFIX,
+            }
+        );
+    }
+
+    #[test]
+    fn skips_whole_cycles_up_to_the_target() {
+        let (cycle, step) = detect();
+        let (cycles, remaining) = cycle.skip(step, 20);
+        assert_eq!((cycles, remaining), (3, 1));
+        let per_cycle = 2 * step - cycle.start_value;
+        let within = 2 * (cycle.start + remaining) - cycle.start_value;
+        assert_eq!(2 * step + cycles * per_cycle + within, 2 * 20);
+        assert_eq!(cycle.skip(step, step + PERIOD), (1, 0));
+        assert_eq!(cycle.skip(step, step - 1), (0, 0));
+    }
+
+    #[test]
+    fn keeps_the_value_first_recorded() {
+        let mut detector = CycleDetector::default();
+        assert_eq!(detector.record(0, 'a', 1), None);
+        assert_eq!(detector.record(1, 'b', 2), None);
+        let cycle = detector.record(5, 'a', 9).unwrap();
+        assert_eq!((cycle.start, cycle.length, cycle.start_value), (0, 5, 1));
+        assert_eq!(
+            detector.record(6, 'a', 9).map(|cycle| cycle.length),
+            Some(6)
+        );
+    }
+}
@@ -1,33 +1,39 @@
 use crate::{
-    check_result,
+    answer::{Answer, PuzzleResult},
+    input::{Paragraph, Puzzle},
+    parse::{parse_number, ParseError},
+    registry::{DayDef, Registry, RunStyle},
     utils::{Context, Part},
 };
-pub fn parse(lines: &Vec<String>) -> Vec<i32> {
-    return lines
-        .into_iter()
-        .map(|l| {
-            if l.len() == 0 {
-                -1
-            } else {
-                l.parse::<i32>().ok().unwrap_or(-1)
-            }
+pub fn parse(paragraphs: &[Paragraph]) -> Result<Vec<i32>, ParseError> {
+    return paragraphs
+        .iter()
+        .map(|paragraph| {
+            paragraph
+                .lines()
+                .map(|(pos, l)| parse_number::<i32>(pos, 0, l))
+                .sum()
         })
         .collect();
 }
 
-pub fn puzzle(context: &Context, lines: &Vec<String>) {
-    let values = parse(lines);
-
-    let mut grouped_sum: Vec<i32> = values
-        .split(|v| *v < 0)
-        .map(|group| group.iter().filter(|v| **v >= 0).sum())
-        .collect();
+pub fn puzzle(context: &Context, paragraphs: &[Paragraph]) -> PuzzleResult {
+    let mut grouped_sum = parse(paragraphs)?;
 
     grouped_sum.sort_by(|a, b| b.cmp(a));
     if context.is_part(Part::Part1) {
-        check_result!(context, grouped_sum[0], [24000, 74394]);
+        Ok(Answer::single(grouped_sum[0]))
     } else {
         let total: i32 = grouped_sum.into_iter().take(3).sum();
-        check_result!(context, total, [45000, 212836]);
+        Ok(Answer::single(total))
     }
 }
+
+pub fn register(registry: &mut Registry) {
+    registry.register(DayDef {
+        day: 1,
+        title: "Calorie Counting",
+        puzzle: Puzzle::Paragraphs(puzzle),
+        run_style: RunStyle::PerPart,
+    });
+}
@@ -1,6 +1,10 @@
 use crate::{
+    answer::{Answer, PuzzleResult},
+    input::{Paragraph, Puzzle},
     log,
-    utils::{Context, Part}, check_result,
+    parse::{captures, group, offset_in, parse_group, parse_number, ParseError},
+    registry::{DayDef, Registry, RunStyle},
+    utils::{Context, Part},
 };
 use lazy_static::lazy_static;
 use regex::Regex;
@@ -34,69 +38,72 @@ lazy_static! {
         Regex::new(r"^\s*Test: divisible by (?P<val>\d+)$").unwrap();
 }
 
-fn parse_operation(line: &String) -> Operation {
-    let operation_parsed = OPERATION_REGEXP.captures(line.as_str()).unwrap();
-    let other_operand = operation_parsed.name("other").unwrap().as_str();
-    let other_operand_num = if other_operand == "old" {
+fn parse_operation(pos: usize, line: &str) -> Result<Operation, ParseError> {
+    let operation_parsed = captures(&OPERATION_REGEXP, pos, 0, line, "an operation")?;
+    let other_operand = group(pos, 0, &operation_parsed, "other")?;
+    let other_operand_num = if other_operand.as_str() == "old" {
         None
     } else {
-        Some(other_operand.parse::<u64>().unwrap())
+        Some(parse_number::<u64>(pos, other_operand.start(), other_operand.as_str())?)
     };
-    let operator = operation_parsed.name("op").unwrap().as_str();
-    match operator {
-        "*" => match other_operand_num {
+    let operator = group(pos, 0, &operation_parsed, "op")?;
+    match operator.as_str() {
+        "*" => Ok(match other_operand_num {
             Some(val) => Operation::Multiply(val),
             None => Operation::Square,
-        },
-        "+" => match other_operand_num {
+        }),
+        "+" => Ok(match other_operand_num {
             Some(val) => Operation::Add(val),
             None => Operation::Double,
-        },
-        _ => panic!("Unknown operator {}", operator),
+        }),
+        _ => Err(ParseError::new(pos, operator.start(), operator.as_str(), "unknown operator")),
     }
 }
 
-fn parse_target(line: &String) -> usize {
-    MONKEY_TARGET_REGEXP
-        .captures(line.as_str())
-        .unwrap()
-        .name("target")
-        .map(|target| target.as_str().parse::<usize>().unwrap())
-        .unwrap()
+fn parse_target(pos: usize, line: &str) -> Result<usize, ParseError> {
+    let target = captures(&MONKEY_TARGET_REGEXP, pos, 0, line, "a target monkey")?;
+    parse_group(pos, 0, &target, "target")
 }
 
-fn parse_monkey(pos: usize, lines: &[String]) -> Monkey {
-    let starting_items: Vec<u64> = STARTING_ITEMS_REGEXP
-        .captures(lines[1].as_str())
-        .unwrap()
-        .name("items")
-        .unwrap()
+fn parse_monkey(id: usize, paragraph: &Paragraph) -> Result<Monkey, ParseError> {
+    let lines: Vec<(usize, &str)> = paragraph.lines().collect();
+    let line = |index: usize| {
+        lines.get(index).copied().ok_or_else(|| {
+            ParseError::new(paragraph.first_line + lines.len(), 0, "", "incomplete monkey description")
+        })
+    };
+    let (pos, items_line) = line(1)?;
+    let items = captures(&STARTING_ITEMS_REGEXP, pos, 0, items_line, "starting items")?;
+    let items = group(pos, 0, &items, "items")?;
+    let starting_items: Vec<u64> = items
         .as_str()
         .split(", ")
-        .map(|item| item.parse::<u64>().unwrap())
-        .collect();
+        .map(|item| parse_number::<u64>(pos, items.start() + offset_in(items.as_str(), item), item))
+        .collect::<Result<_, _>>()?;
 
-    Monkey {
-        id: pos,
+    let (pos, condition_line) = line(3)?;
+    let condition = captures(&CONDITION_REGEXP, pos, 0, condition_line, "a divisibility test")?;
+    let (operation_pos, operation_line) = line(2)?;
+    let (target_true_pos, target_true_line) = line(4)?;
+    let (target_false_pos, target_false_line) = line(5)?;
+    Ok(Monkey {
+        id,
         items: starting_items,
         nb_processed_items: 0,
-        test_divisibility: CONDITION_REGEXP
-            .captures(lines[3].as_str())
-            .unwrap()
-            .name("val")
-            .map(|val| val.as_str().parse::<u64>().unwrap())
-            .unwrap(),
-        targets: (parse_target(&lines[4]), parse_target(&lines[5])),
-        operation: parse_operation(&lines[2]),
-    }
+        test_divisibility: parse_group(pos, 0, &condition, "val")?,
+        targets: (
+            parse_target(target_true_pos, target_true_line)?,
+            parse_target(target_false_pos, target_false_line)?,
+        ),
+        operation: parse_operation(operation_pos, operation_line)?,
+    })
 }
 
-fn parse(lines: &Vec<String>) -> Vec<Monkey> {
-    lines
-        .as_slice()
-        .split(|line| line.len() == 0)
+fn parse(paragraphs: &[Paragraph]) -> Result<Vec<Monkey>, ParseError> {
+    paragraphs
+        .iter()
         .enumerate()
-        .map(|(pos, lines)| parse_monkey(pos, lines))
+        .map(|(id, paragraph)| parse_monkey(id, paragraph))
         .collect()
 }
 
@@ -131,8 +138,8 @@ fn iterate(monkeys: &mut Vec<Monkey>, is_part1: bool, combined_dividers: u64,new
     }
 }
 
-pub fn puzzle(context: &Context, lines: &Vec<String>) {
-    let mut monkeys = parse(lines);
+pub fn puzzle(context: &Context, paragraphs: &[Paragraph]) -> PuzzleResult {
+    let mut monkeys = parse(paragraphs)?;
     let is_part1 = context.is_part(Part::Part1);
     let combined_dividers: u64 = monkeys.iter().map(|m| &m.test_divisibility).product();
     let mut temps:Vec<Vec<u64>> = Vec::with_capacity(monkeys.len());
@@ -145,12 +152,21 @@ pub fn puzzle(context: &Context, lines: &Vec<String>) {
             iterate(&mut monkeys, is_part1, combined_dividers,&mut temps)
         }
         monkeys.sort_unstable_by(|a,b| b.nb_processed_items.cmp(&a.nb_processed_items));
-        check_result!(context,monkeys[0].nb_processed_items * monkeys[1].nb_processed_items, [10605, 117640]);
+        Ok(Answer::single(monkeys[0].nb_processed_items * monkeys[1].nb_processed_items))
     } else {
         for _ in 0..10000 {
             iterate(&mut monkeys, is_part1, combined_dividers,&mut temps)
         }
         monkeys.sort_unstable_by(|a,b| b.nb_processed_items.cmp(&a.nb_processed_items));
-        check_result!(context,monkeys[0].nb_processed_items * monkeys[1].nb_processed_items, [2713310158, 30616425600]);
+        Ok(Answer::single(monkeys[0].nb_processed_items * monkeys[1].nb_processed_items))
     }
 }
+
+pub fn register(registry: &mut Registry) {
+    registry.register(DayDef {
+        day: 11,
+        title: "Monkey in the Middle",
+        puzzle: Puzzle::Paragraphs(puzzle),
+        run_style: RunStyle::PerPart,
+    });
+}
@@ -0,0 +1,200 @@
+use std::{cmp::Ordering, fmt, slice};
+
+use crate::{
+    answer::{Answer, PuzzleResult},
+    input::{Paragraph, Puzzle},
+    log,
+    parse::{parse_number, ParseError},
+    registry::{DayDef, Registry, RunStyle},
+    utils::{Context, Part},
+};
+
+/// Packet value, a number being compared to a list as the list holding only this number
+#[derive(Debug, Clone)]
+enum Packet {
+    Int(u32),
+    List(Vec<Packet>),
+}
+
+impl Ord for Packet {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (Packet::Int(left), Packet::Int(right)) => left.cmp(right),
+            (Packet::List(left), Packet::List(right)) => left.cmp(right),
+            (Packet::Int(_), Packet::List(right)) => slice::from_ref(self).cmp(right.as_slice()),
+            (Packet::List(left), Packet::Int(_)) => left.as_slice().cmp(slice::from_ref(other)),
+        }
+    }
+}
+
+impl PartialOrd for Packet {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Packet {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Packet {}
+
+impl fmt::Display for Packet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Packet::Int(value) => write!(f, "{}", value),
+            Packet::List(items) => {
+                write!(f, "[")?;
+                for (pos, item) in items.iter().enumerate() {
+                    if pos > 0 {
+                        write!(f, ",")?;
+                    }
+                    write!(f, "{}", item)?;
+                }
+                write!(f, "]")
+            }
+        }
+    }
+}
+
+/// Recursive descent parser reading a packet straight from its line
+struct PacketParser<'a> {
+    line_index: usize,
+    line: &'a str,
+    offset: usize,
+}
+
+impl<'a> PacketParser<'a> {
+    fn parse(line_index: usize, line: &'a str) -> Result<Packet, ParseError> {
+        let mut parser = PacketParser {
+            line_index,
+            line,
+            offset: 0,
+        };
+        let packet = parser.parse_list()?;
+        if parser.offset < line.len() {
+            return Err(parser.error("unexpected text after the packet"));
+        }
+        Ok(packet)
+    }
+
+    fn error(&self, message: &str) -> ParseError {
+        let text = self.line.get(self.offset..self.offset + 1).unwrap_or("");
+        ParseError::new(self.line_index, self.offset, text, message)
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.line.as_bytes().get(self.offset).copied()
+    }
+
+    fn expect(&mut self, expected: u8, message: &str) -> Result<(), ParseError> {
+        if self.peek() != Some(expected) {
+            return Err(self.error(message));
+        }
+        self.offset += 1;
+        Ok(())
+    }
+
+    fn parse_list(&mut self) -> Result<Packet, ParseError> {
+        self.expect(b'[', "expecting [")?;
+        let mut items = vec![];
+        if self.peek() == Some(b']') {
+            self.offset += 1;
+            return Ok(Packet::List(items));
+        }
+        loop {
+            items.push(self.parse_value()?);
+            match self.peek() {
+                Some(b',') => self.offset += 1,
+                Some(b']') => {
+                    self.offset += 1;
+                    return Ok(Packet::List(items));
+                }
+                _ => return Err(self.error("expecting , or ]")),
+            }
+        }
+    }
+
+    fn parse_value(&mut self) -> Result<Packet, ParseError> {
+        match self.peek() {
+            Some(b'[') => self.parse_list(),
+            Some(b'0'..=b'9') => {
+                let start = self.offset;
+                while let Some(b'0'..=b'9') = self.peek() {
+                    self.offset += 1;
+                }
+                let text = &self.line[start..self.offset];
+                Ok(Packet::Int(parse_number(self.line_index, start, text)?))
+            }
+            _ => Err(self.error("expecting a number or a list")),
+        }
+    }
+}
+
+fn parse(paragraphs: &[Paragraph]) -> Result<Vec<(Packet, Packet)>, ParseError> {
+    paragraphs
+        .iter()
+        .map(|paragraph| {
+            let packets = paragraph
+                .lines()
+                .map(|(line_index, line)| PacketParser::parse(line_index, line))
+                .collect::<Result<Vec<_>, _>>()?;
+            match <[Packet; 2]>::try_from(packets) {
+                Ok([left, right]) => Ok((left, right)),
+                Err(_) => Err(ParseError::line(
+                    paragraph.first_line,
+                    paragraph.text,
+                    "expecting a pair of packets",
+                )),
+            }
+        })
+        .collect()
+}
+
+pub fn puzzle(context: &Context, paragraphs: &[Paragraph]) -> PuzzleResult {
+    let pairs = parse(paragraphs)?;
+    if context.is_part(Part::Part1) {
+        let result: usize = pairs
+            .iter()
+            .enumerate()
+            .filter(|(_, (left, right))| left <= right)
+            .map(|(pos, _)| pos + 1)
+            .sum();
+        Ok(Answer::single(result))
+    } else {
+        let dividers = [
+            PacketParser::parse(0, "[[2]]")?,
+            PacketParser::parse(0, "[[6]]")?,
+        ];
+        // dividers are told apart by a flag, other packets being possibly equal to them
+        let mut packets: Vec<(Packet, bool)> = pairs
+            .into_iter()
+            .flat_map(|(left, right)| [(left, false), (right, false)])
+            .chain(dividers.into_iter().map(|divider| (divider, true)))
+            .collect();
+        packets.sort();
+        if context.is_debug() && context.is_test() {
+            for (packet, _) in &packets {
+                log!(debug, context, "{}", packet);
+            }
+        }
+        let result: usize = packets
+            .iter()
+            .enumerate()
+            .filter(|(_, (_, is_divider))| *is_divider)
+            .map(|(pos, _)| pos + 1)
+            .product();
+        Ok(Answer::single(result))
+    }
+}
+
+pub fn register(registry: &mut Registry) {
+    registry.register(DayDef {
+        day: 13,
+        title: "Distress Signal",
+        puzzle: Puzzle::Paragraphs(puzzle),
+        run_style: RunStyle::PerPart,
+    });
+}
@@ -0,0 +1,165 @@
+use lazy_static::lazy_static;
+use regex::Regex;
+
+use crate::{
+    answer::{Answer, PuzzleResult},
+    input::Puzzle,
+    interval::{Interval, IntervalSet},
+    log,
+    parse::{captures, parse_group, ParseError},
+    registry::{DayDef, Registry, RunStyle},
+    utils::{Context, Part},
+};
+
+lazy_static! {
+    static ref SENSOR_PARSE_REGEX: Regex = Regex::new(
+        r"^Sensor at x=(?P<sx>-?\d+), y=(?P<sy>-?\d+): closest beacon is at x=(?P<bx>-?\d+), y=(?P<by>-?\d+)$"
+    )
+    .unwrap();
+}
+
+#[derive(Debug)]
+struct Sensor {
+    x: i64,
+    y: i64,
+    beacon: (i64, i64),
+    /// Distance to the closest beacon, no other beacon being that close
+    range: i64,
+}
+
+impl Sensor {
+    /// Coordinates in the 45° rotated space where the range of the sensor is a square
+    fn rotated(&self) -> (i64, i64) {
+        (self.x + self.y, self.x - self.y)
+    }
+}
+
+fn parse(lines: &[String]) -> Result<Vec<Sensor>, ParseError> {
+    lines
+        .iter()
+        .enumerate()
+        .map(|(pos, line)| {
+            let c = captures(&SENSOR_PARSE_REGEX, pos, 0, line, "a sensor description")?;
+            let (x, y) = (
+                parse_group(pos, 0, &c, "sx")?,
+                parse_group(pos, 0, &c, "sy")?,
+            );
+            let beacon: (i64, i64) = (
+                parse_group(pos, 0, &c, "bx")?,
+                parse_group(pos, 0, &c, "by")?,
+            );
+            Ok(Sensor {
+                x,
+                y,
+                beacon,
+                range: (x - beacon.0).abs() + (y - beacon.1).abs(),
+            })
+        })
+        .collect()
+}
+
+fn row_coverage(sensors: &[Sensor], row: i64) -> IntervalSet {
+    let mut covered = IntervalSet::new();
+    for sensor in sensors {
+        let x_distance = sensor.range - (sensor.y - row).abs();
+        if x_distance >= 0 {
+            covered.insert(Interval::new(sensor.x - x_distance, sensor.x + x_distance));
+        }
+    }
+    covered
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Axis {
+    U,
+    V,
+}
+
+/// Cells of the search square not covered by any sensor on the rotated line `axis = value`, the
+/// line being a diagonal of the original space
+fn uncovered_on_line(sensors: &[Sensor], axis: Axis, value: i64, max: i64) -> Option<(i64, i64)> {
+    let mut covered = IntervalSet::new();
+    for sensor in sensors {
+        let (u, v) = sensor.rotated();
+        let (line, other) = if axis == Axis::U { (u, v) } else { (v, u) };
+        if (line - value).abs() <= sensor.range {
+            covered.insert(Interval::new(other - sensor.range, other + sensor.range));
+        }
+    }
+    // x = (u + v) / 2 and y = (u - v) / 2 both in [0, max]
+    let (start, end) = match axis {
+        Axis::U => ((-value).max(value - 2 * max), (2 * max - value).min(value)),
+        Axis::V => ((-value).max(value), (2 * max - value).min(2 * max + value)),
+    };
+    if start > end {
+        return None;
+    }
+    let within = Interval::new(start, end);
+    covered
+        .gaps(within)
+        .iter()
+        .flat_map(|gap| gap.start..=gap.end)
+        .find(|other| (value + other) % 2 == 0)
+        .map(|other| {
+            let (u, v) = if axis == Axis::U {
+                (value, other)
+            } else {
+                (other, value)
+            };
+            ((u + v) / 2, (u - v) / 2)
+        })
+}
+
+/// The only uncovered cell is next to the border of a sensor range, so on one of the lines just
+/// outside of a range in the rotated space
+fn find_uncovered(sensors: &[Sensor], max: i64) -> Option<(i64, i64)> {
+    sensors
+        .iter()
+        .flat_map(|sensor| {
+            let (u, v) = sensor.rotated();
+            let outside = sensor.range + 1;
+            [
+                (Axis::U, u - outside),
+                (Axis::U, u + outside),
+                (Axis::V, v - outside),
+                (Axis::V, v + outside),
+            ]
+        })
+        .find_map(|(axis, value)| uncovered_on_line(sensors, axis, value, max))
+}
+
+pub fn puzzle(context: &Context, lines: &[String]) -> PuzzleResult {
+    let sensors = parse(lines)?;
+    if context.is_part(Part::Part1) {
+        let row = if context.is_test() { 10 } else { 2000000 };
+        let mut covered = row_coverage(&sensors, row);
+        for sensor in &sensors {
+            if sensor.beacon.1 == row {
+                covered.subtract(Interval::single(sensor.beacon.0));
+            }
+        }
+        log!(debug, context, "Row {} without beacon: {}", row, covered);
+        Ok(Answer::single(covered.covered_len()))
+    } else {
+        let max = if context.is_test() { 20 } else { 4000000 };
+        let (x, y) = find_uncovered(&sensors, max).ok_or_else(|| {
+            ParseError::new(
+                lines.len(),
+                0,
+                "",
+                "no uncovered position for the distress beacon",
+            )
+        })?;
+        log!(debug, context, "Distress beacon at x={}, y={}", x, y);
+        Ok(Answer::single(x * 4000000 + y))
+    }
+}
+
+pub fn register(registry: &mut Registry) {
+    registry.register(DayDef {
+        day: 15,
+        title: "Beacon Exclusion Zone",
+        puzzle: Puzzle::Lines(puzzle),
+        run_style: RunStyle::PerPart,
+    });
+}
@@ -3,8 +3,13 @@ use lazy_static::lazy_static;
 use regex::Regex;
 
 use crate::{
-    check_result, log,
-    priority_queue::{Cost, Key, PriorityQueue},
+    answer::{Answer, PuzzleResult},
+    input::Puzzle,
+    log,
+    parse::{captures, group, offset_in, parse_group, ParseError},
+    priority_queue::{Cost, Key, MaxFirst, PriorityQueue},
+    registry::{DayDef, Registry, RunStyle},
+    search::{self, Tracking},
     utils::Context,
 };
 
@@ -20,27 +25,42 @@ lazy_static! {
     static ref VALVE_PARSE_REGEX:Regex =  Regex::new(r"^Valve (?P<name>\w+) has flow rate=(?P<rate>\d+); tunnels? leads? to valves? (?P<list>\w+(?:, \w+)*)$").unwrap();
 }
 
-fn parse(lines: &Vec<String>) -> Vec<ValveDef> {
-    lines
-        .into_iter()
+/// Valve of the line with the offsets of its tunnel names in the line
+fn parse_valve(pos: usize, line: &str) -> Result<(ValveDef, Vec<usize>), ParseError> {
+    let c = captures(&VALVE_PARSE_REGEX, pos, 0, line, "a valve description")?;
+    let list = group(pos, 0, &c, "list")?;
+    let (connected_to, offsets) = list
+        .as_str()
+        .split(", ")
+        .map(|name| (String::from(name), list.start() + offset_in(list.as_str(), name)))
+        .unzip();
+    let valve = ValveDef {
+        id: pos as u8,
+        name: String::from(group(pos, 0, &c, "name")?.as_str()),
+        flow_rate: parse_group(pos, 0, &c, "rate")?,
+        connected_to,
+    };
+    Ok((valve, offsets))
+}
+
+fn parse(lines: &[String]) -> Result<Vec<ValveDef>, ParseError> {
+    let valves = lines
+        .iter()
         .enumerate()
-        .map(|(pos, line)| {
-            let c = VALVE_PARSE_REGEX.captures(line).unwrap();
-            ValveDef {
-                id: pos as u8,
-                name: String::from(c.name("name").unwrap().as_str()),
-                flow_rate: c.name("rate").unwrap().as_str().parse::<u16>().unwrap(),
-                connected_to: c
-                    .name("list")
-                    .unwrap()
-                    .as_str()
-                    .split(", ")
-                    .into_iter()
-                    .map(|s| String::from(s))
-                    .collect(),
+        .map(|(pos, line)| parse_valve(pos, line))
+        .collect::<Result<Vec<_>, _>>()?;
+    let names: HashSet<&str> = valves.iter().map(|(valve, _)| valve.name.as_str()).collect();
+    if !names.contains("AA") {
+        return Err(ParseError::new(lines.len(), 0, "", "missing valve AA"));
+    }
+    for (pos, (valve, offsets)) in valves.iter().enumerate() {
+        for (name, offset) in valve.connected_to.iter().zip(offsets) {
+            if !names.contains(name.as_str()) {
+                return Err(ParseError::new(pos, *offset, name, "unknown valve"));
             }
-        })
-        .collect()
+        }
+    }
+    Ok(valves.into_iter().map(|(valve, _)| valve).collect())
 }
 
 struct Graph<'a> {
@@ -54,15 +74,9 @@ struct PathInfo {
     distance: u16,
 }
 
+#[derive(Clone)]
 struct FindPathState<'a> {
     curr: &'a ValveDef,
-    distance: u16,
-}
-
-impl<'a> Cost<u16> for FindPathState<'a> {
-    fn cost(&self) -> u16 {
-        self.distance
-    }
 }
 
 impl<'a> Key<&'a String> for FindPathState<'a> {
@@ -77,30 +91,26 @@ fn find_sortest_path<'a>(
     all_valves: &'a Vec<ValveDef>,
     map_id: &'a HashMap<String, u8>,
 ) -> PathInfo {
-    let mut priority_queue: PriorityQueue<u16, &'a String, FindPathState> = PriorityQueue::new();
-    priority_queue.push(FindPathState {
-        curr: from,
-        distance: 0,
-    });
-
-    while let Some(s) = priority_queue.pop() {
-        if s.curr.name == *to.name {
-            return PathInfo {
-                distance: s.distance,
-            };
-        }
-        for link in &s.curr.connected_to {
-            priority_queue.push(FindPathState {
-                curr: &all_valves[*map_id.get(link).unwrap() as usize],
-                distance: s.distance + 1,
+    let result = search::dijkstra(
+        Tracking::CostOnly,
+        FindPathState { curr: from },
+        |s| {
+            s.curr.connected_to.iter().map(|link| {
+                let next = FindPathState {
+                    curr: &all_valves[*map_id.get(link).unwrap() as usize],
+                };
+                (next, 1)
             })
-        }
-    }
+        },
+        |s| s.curr.name == *to.name,
+    );
 
-    PathInfo { distance: 0 }
+    PathInfo {
+        distance: result.cost().unwrap_or(0),
+    }
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 struct AgentState {
     target_id: u8,
     remaining_distance: i16,
@@ -138,7 +148,7 @@ fn open_reached_node_for_id(state: &mut State, id: u8, graph: &Graph) {
     state.cumul_flow_rate += valve.flow_rate;
 }
 
-fn open_reached_node(state: &mut State, graph: &Graph, context: &mut DfsContext) {
+fn open_reached_node(state: &mut State, graph: &Graph, context: &mut SearchContext) {
     let need_open_0 = !state.agents.0.done && state.agents.0.remaining_distance == 0;
     let need_open_1 = !state.agents.1.done && state.agents.1.remaining_distance == 0;
     advance_minutes(state, 1);
@@ -158,7 +168,7 @@ fn open_reached_node(state: &mut State, graph: &Graph, context: &mut DfsContext)
     }
 }
 
-fn move_to_next_nearest_target(state: &mut State, graph: &Graph, context: &mut DfsContext) {
+fn move_to_next_nearest_target(state: &mut State, graph: &Graph, context: &mut SearchContext) {
     let dist_for_first = if !state.agents.0.done {
         Some(state.agents.0.remaining_distance)
     } else {
@@ -206,7 +216,7 @@ fn build_new_state_with_valve_to_visit(state: &State, to_visit: &ValveToVisit) -
     }
 }
 
-struct DfsContext {
+struct SearchContext {
     best_released: u16,
     nb_node_evaluated: u32,
     nb_skipped: u32,
@@ -297,7 +307,7 @@ fn calc_potential_released(state: &State, graph: &Graph) -> u16 {
 fn build_new_state_to_reach_next_target(
     state: &State,
     graph: &Graph,
-    context: &mut DfsContext,
+    context: &mut SearchContext,
 ) -> State {
     let mut new_state = State {
         agents: (
@@ -417,7 +427,7 @@ fn get_next_raw_possible_states(state: &State, graph: &Graph, is_start: bool) ->
 fn get_next_possible_states(
     state: &State,
     graph: &Graph,
-    dfs_context: &mut DfsContext,
+    search_context: &mut SearchContext,
     is_start: bool,
 ) -> Vec<State> {
     let mut next_raw_possible_states = get_next_raw_possible_states(state, graph, is_start);
@@ -427,7 +437,7 @@ fn get_next_possible_states(
             return vec![build_new_state_to_reach_next_target(
                 state,
                 graph,
-                dfs_context,
+                search_context,
             )];
         }
         return vec![build_new_state_to_reach_0_minutes(state)];
@@ -435,68 +445,85 @@ fn get_next_possible_states(
 
     next_raw_possible_states
         .iter_mut()
-        .for_each(|s| move_to_next_nearest_target(s, graph, dfs_context));
+        .for_each(|s| move_to_next_nearest_target(s, graph, search_context));
 
     next_raw_possible_states
         .into_iter()
         .filter(|s| {
             let potential_released = calc_potential_released(&s, graph);
-            let to_keep = potential_released > dfs_context.best_released;
+            let to_keep = potential_released > search_context.best_released;
             if !to_keep {
-                dfs_context.nb_skipped += 1;
+                search_context.nb_skipped += 1;
             }
             to_keep
         })
         .collect()
 }
 
-fn explore(
-    state: &State,
-    graph: &Graph,
-    dfs_context: &mut DfsContext,
-    is_start: bool,
-    context: &Context,
-) -> u16 {
-    dfs_context.nb_node_evaluated += 1;
-    if context.is_debug() {
-        let not_opened: Vec<&String> = state
+/// State queued with the most pressure it could release
+struct Candidate {
+    state: State,
+    potential: u16,
+}
+
+impl Candidate {
+    fn new(state: State, graph: &Graph) -> Candidate {
+        Candidate {
+            potential: calc_potential_released(&state, graph),
+            state,
+        }
+    }
+}
+
+impl Cost<u16> for Candidate {
+    fn cost(&self) -> u16 {
+        self.potential
+    }
+}
+
+/// Agents, valves left to open as a bit set, flow rate, released pressure and remaining minutes
+type StateKey = (AgentState, AgentState, u64, u16, u16, u16);
+
+impl Key<StateKey> for Candidate {
+    fn key(&self) -> StateKey {
+        let state = &self.state;
+        let to_open = state
             .valves_to_open
             .iter()
-            .map(|t| &graph.map[*t as usize].name)
-            .collect();
-        log!(
-            debug,
-            context,
-            "Exploring {} / {} # {} / {} # {} / {:?}",
-            dfs_context.best_released,
-            state.remaining_minutes,
+            .fold(0, |set, id| set | 1 << id);
+        (
+            state.agents.0,
+            state.agents.1,
+            to_open,
+            state.cumul_flow_rate,
             state.released,
-            graph.map[state.agents.0.target_id as usize].name,
-            graph.map[state.agents.1.target_id as usize].name,
-            not_opened
-        );
-    }
-    if state.remaining_minutes == 0 {
-        dfs_context.best_released = std::cmp::max(dfs_context.best_released, state.released);
-        return state.released;
-    }
-    let mut max_released: u16 = 0;
-    for next_state in get_next_possible_states(state, graph, dfs_context, is_start) {
-        let result = explore(&next_state, graph, dfs_context, false, context);
-        if result > max_released {
-            max_released = result;
-            dfs_context.best_released = std::cmp::max(dfs_context.best_released, max_released);
-        }
+            state.remaining_minutes,
+        )
     }
-    max_released
 }
 
-fn dfs_max_release(
-    graph: &Graph,
-    max_duration: u16,
-    is_second_part: bool,
-    context: &Context,
-) -> u16 {
+fn log_explored(state: &State, graph: &Graph, search_context: &SearchContext, context: &Context) {
+    let not_opened: Vec<&String> = state
+        .valves_to_open
+        .iter()
+        .map(|t| &graph.map[*t as usize].name)
+        .collect();
+    log!(
+        debug,
+        context,
+        "Exploring {} / {} # {} / {} # {} / {:?}",
+        search_context.best_released,
+        state.remaining_minutes,
+        state.released,
+        graph.map[state.agents.0.target_id as usize].name,
+        graph.map[state.agents.1.target_id as usize].name,
+        not_opened
+    );
+}
+
+/// Best-first branch and bound: once the highest potential left is not above the best release
+/// reached, no other state can do better
+fn max_release(graph: &Graph, max_duration: u16, is_second_part: bool, context: &Context) -> u16 {
     let start = graph.map[*graph.map_id.get("AA").unwrap() as usize].id;
     let init_state: State = State {
         agents: (
@@ -518,27 +545,47 @@ fn dfs_max_release(
         released: 0,
         remaining_minutes: max_duration,
     };
-    let mut dfs_context = DfsContext {
+    let mut search_context = SearchContext {
         best_released: 0,
-        nb_node_evaluated: 0,
+        nb_node_evaluated: 1,
         nb_skipped: 0,
         nb_dual_opened: 0,
     };
 
-    let max = explore(&init_state, graph, &mut dfs_context, true, context);
+    let mut queue: PriorityQueue<u16, StateKey, Candidate, MaxFirst> = PriorityQueue::new();
+    // agents starting together, the first expansion skips the states only swapping them
+    for state in get_next_possible_states(&init_state, graph, &mut search_context, true) {
+        queue.push(Candidate::new(state, graph));
+    }
+    while let Some(Candidate { state, potential }) = queue.pop() {
+        if potential <= search_context.best_released {
+            break;
+        }
+        search_context.nb_node_evaluated += 1;
+        if context.is_debug() {
+            log_explored(&state, graph, &search_context, context);
+        }
+        if state.remaining_minutes == 0 {
+            search_context.best_released = std::cmp::max(search_context.best_released, state.released);
+            continue;
+        }
+        for next_state in get_next_possible_states(&state, graph, &mut search_context, false) {
+            queue.push(Candidate::new(next_state, graph));
+        }
+    }
     log!(
         debug,
         context,
         "Node explored {} / {} skipped / {} double opened",
-        dfs_context.nb_node_evaluated,
-        dfs_context.nb_skipped,
-        dfs_context.nb_dual_opened
+        search_context.nb_node_evaluated,
+        search_context.nb_skipped,
+        search_context.nb_dual_opened
     );
-    max
+    search_context.best_released
 }
 
-pub fn puzzle(context: &Context, lines: &Vec<String>) {
-    let map: Vec<ValveDef> = parse(lines).into_iter().collect();
+pub fn puzzle(context: &Context, lines: &[String]) -> PuzzleResult {
+    let map: Vec<ValveDef> = parse(lines)?;
     let map_id: HashMap<String, u8> = map
         .iter()
         .map(|v| (v.name.to_string(), v.id))
@@ -551,14 +598,10 @@ pub fn puzzle(context: &Context, lines: &Vec<String>) {
         paths: map_path,
     };
 
-    check_result!(
-        context,
-        [
-            dfs_max_release(&graph, 30, false, context),
-            dfs_max_release(&graph, 26, true, context)
-        ],
-        [1651, 2029, 1707, 2723]
-    );
+    Ok(Answer::pair(
+        max_release(&graph, 30, false, context),
+        max_release(&graph, 26, true, context),
+    ))
 }
 
 fn get_valves_map_to_open(map: &Vec<ValveDef>) -> Vec<u8> {
@@ -582,3 +625,12 @@ fn build_paths_map(
         })
         .collect();
 }
+
+pub fn register(registry: &mut Registry) {
+    registry.register(DayDef {
+        day: 16,
+        title: "Proboscidea Volcanium",
+        puzzle: Puzzle::Lines(puzzle),
+        run_style: RunStyle::Simult,
+    });
+}
@@ -0,0 +1,244 @@
+use lazy_static::lazy_static;
+
+use crate::{
+    answer::{Answer, PuzzleResult},
+    cycle::CycleDetector,
+    input::Puzzle,
+    log,
+    parse::ParseError,
+    registry::{DayDef, Registry, RunStyle},
+    utils::{Context, Part},
+};
+
+const WIDTH: usize = 7;
+const DELTA_FROM_LEFT: usize = 2;
+const DELTA_FROM_TOP: usize = 3;
+const LEFT_WALL: u8 = 1 << (WIDTH - 1);
+const RIGHT_WALL: u8 = 1;
+
+const SHAPES: [&[&str]; 5] = [
+    &["####"],
+    &[".#.", "###", ".#."],
+    &["..#", "..#", "###"],
+    &["#", "#", "#", "#"],
+    &["##", "##"],
+];
+
+lazy_static! {
+    static ref ROCKS: Vec<Rock> = SHAPES.iter().map(|shape| Rock::parse(shape)).collect();
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Jet {
+    Left,
+    Right,
+}
+
+/// Rows of a rock from its bottom, the leftmost column of the chamber being the highest bit
+#[derive(Debug, Clone, Copy)]
+struct Rock {
+    rows: [u8; 4],
+    height: usize,
+}
+
+impl Rock {
+    fn parse(shape: &[&str]) -> Rock {
+        let mut rows = [0; 4];
+        for (pos, line) in shape.iter().rev().enumerate() {
+            rows[pos] = line
+                .bytes()
+                .enumerate()
+                .filter(|(_, c)| *c == b'#')
+                .fold(0, |row, (column, _)| {
+                    row | 1 << (WIDTH - 1 - DELTA_FROM_LEFT - column)
+                });
+        }
+        Rock {
+            rows,
+            height: shape.len(),
+        }
+    }
+
+    fn rows(&self) -> &[u8] {
+        &self.rows[..self.height]
+    }
+
+    /// The rock pushed by the jet, unless it hits a wall
+    fn pushed(&self, jet: Jet) -> Option<Rock> {
+        let (wall, shift): (u8, fn(u8) -> u8) = match jet {
+            Jet::Left => (LEFT_WALL, |row| row << 1),
+            Jet::Right => (RIGHT_WALL, |row| row >> 1),
+        };
+        if self.rows().iter().any(|row| row & wall != 0) {
+            return None;
+        }
+        let mut rows = self.rows;
+        rows.iter_mut().for_each(|row| *row = shift(*row));
+        Some(Rock { rows, ..*self })
+    }
+}
+
+/// Fingerprint of the chamber: next rock, next jet and depth of the top of each column
+type State = (usize, usize, [usize; WIDTH]);
+
+#[derive(Debug)]
+struct Chamber<'a> {
+    jets: &'a [Jet],
+    /// Rows from the floor, the top one holding at least a block
+    rows: Vec<u8>,
+    next_rock: usize,
+    next_jet: usize,
+}
+
+impl<'a> Chamber<'a> {
+    fn new(jets: &'a [Jet]) -> Chamber<'a> {
+        Chamber {
+            jets,
+            rows: vec![],
+            next_rock: 0,
+            next_jet: 0,
+        }
+    }
+
+    fn height(&self) -> usize {
+        self.rows.len()
+    }
+
+    fn collides(&self, rock: &Rock, bottom: usize) -> bool {
+        rock.rows()
+            .iter()
+            .enumerate()
+            .any(|(pos, row)| self.rows.get(bottom + pos).unwrap_or(&0) & row != 0)
+    }
+
+    fn drop_rock(&mut self) {
+        let mut rock = ROCKS[self.next_rock];
+        self.next_rock = (self.next_rock + 1) % ROCKS.len();
+        let mut bottom = self.height() + DELTA_FROM_TOP;
+        loop {
+            let jet = self.jets[self.next_jet];
+            self.next_jet = (self.next_jet + 1) % self.jets.len();
+            if let Some(pushed) = rock.pushed(jet) {
+                if !self.collides(&pushed, bottom) {
+                    rock = pushed;
+                }
+            }
+            if bottom == 0 || self.collides(&rock, bottom - 1) {
+                break;
+            }
+            bottom -= 1;
+        }
+        for (pos, row) in rock.rows().iter().enumerate() {
+            if bottom + pos >= self.rows.len() {
+                self.rows.push(0);
+            }
+            self.rows[bottom + pos] |= row;
+        }
+    }
+
+    fn state(&self) -> State {
+        let depths = std::array::from_fn(|column| {
+            let mask = LEFT_WALL >> column;
+            self.rows
+                .iter()
+                .rev()
+                .position(|row| row & mask != 0)
+                .unwrap_or(self.height())
+        });
+        (self.next_rock, self.next_jet, depths)
+    }
+
+    fn render(&self) -> String {
+        let mut lines: Vec<String> = self
+            .rows
+            .iter()
+            .rev()
+            .map(|row| {
+                let cells: String = (0..WIDTH)
+                    .map(|column| {
+                        if row & (LEFT_WALL >> column) != 0 {
+                            '#'
+                        } else {
+                            '.'
+                        }
+                    })
+                    .collect();
+                format!("|{}|", cells)
+            })
+            .collect();
+        lines.push(format!("+{}+", "-".repeat(WIDTH)));
+        lines.join("\n")
+    }
+}
+
+fn parse(text: &str) -> Result<Vec<Jet>, ParseError> {
+    let line = text.lines().next().unwrap_or("");
+    if line.is_empty() {
+        return Err(ParseError::line(0, line, "expecting jets"));
+    }
+    line.char_indices()
+        .map(|(pos, c)| match c {
+            '<' => Ok(Jet::Left),
+            '>' => Ok(Jet::Right),
+            _ => Err(ParseError::new(
+                0,
+                pos,
+                &line[pos..pos + c.len_utf8()],
+                "expecting < or >",
+            )),
+        })
+        .collect()
+}
+
+/// Height of the tower after `count` rocks, skipping the repetitions of the chamber state
+fn tower_height(context: &Context, chamber: &mut Chamber, count: usize) -> usize {
+    let mut detector = CycleDetector::new();
+    for step in 1..=count {
+        chamber.drop_rock();
+        if let Some(cycle) = detector.record(step, chamber.state(), chamber.height()) {
+            let growth = chamber.height() - cycle.start_value;
+            log!(
+                debug,
+                context,
+                "Cycle of {} rocks from rock {}, growing by {}",
+                cycle.length,
+                cycle.start,
+                growth
+            );
+            let (cycles, remaining) = cycle.skip(step, count);
+            for _ in 0..remaining {
+                chamber.drop_rock();
+            }
+            return chamber.height() + cycles * growth;
+        }
+    }
+    chamber.height()
+}
+
+pub fn puzzle(context: &Context, text: &str) -> PuzzleResult {
+    let jets = parse(text)?;
+    let mut chamber = Chamber::new(&jets);
+    if context.is_part(Part::Part1) {
+        if context.is_debug() && context.is_test() {
+            for _ in 0..10 {
+                chamber.drop_rock();
+            }
+            log!(debug, context, "After 10 rocks:\n{}", chamber.render());
+            chamber = Chamber::new(&jets);
+        }
+        let result = tower_height(context, &mut chamber, 2022);
+        Ok(Answer::single(result))
+    } else {
+        let result = tower_height(context, &mut chamber, 1000000000000);
+        Ok(Answer::single(result))
+    }
+}
+
+pub fn register(registry: &mut Registry) {
+    registry.register(DayDef {
+        day: 17,
+        title: "Pyroclastic Flow",
+        puzzle: Puzzle::Text(puzzle),
+        run_style: RunStyle::PerPart,
+    });
+}
@@ -0,0 +1,94 @@
+use crate::{
+    answer::{Answer, PuzzleResult},
+    input::Puzzle,
+    log,
+    parse::{offset_in, parse_number, ParseError},
+    registry::{DayDef, Registry, RunStyle},
+    utils::{Context, Part},
+    voxel::{Coord3, VoxelGrid},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Voxel {
+    Air,
+    Lava,
+}
+
+fn parse_cube(pos: usize, line: &str) -> Result<Coord3, ParseError> {
+    let values = line
+        .split(',')
+        .map(|value| parse_number(pos, offset_in(line, value), value))
+        .collect::<Result<Vec<i32>, _>>()?;
+    match values[..] {
+        [x, y, z] => Ok(Coord3::new(x, y, z)),
+        _ => Err(ParseError::line(pos, line, "expecting x,y,z")),
+    }
+}
+
+/// Droplet in the smallest grid holding all of its cubes
+fn parse(lines: &[String]) -> Result<VoxelGrid<Voxel>, ParseError> {
+    let cubes = lines
+        .iter()
+        .enumerate()
+        .map(|(pos, line)| parse_cube(pos, line))
+        .collect::<Result<Vec<_>, _>>()?;
+    let first = *cubes
+        .first()
+        .ok_or_else(|| ParseError::new(0, 0, "", "expecting cubes"))?;
+    let (min, max) = cubes.iter().fold((first, first), |(min, max), cube| {
+        (min.min(*cube), max.max(*cube))
+    });
+    let mut droplet = VoxelGrid::new(min, max, Voxel::Air)
+        .map_err(|error| ParseError::new(lines.len(), 0, "", error))?;
+    for cube in cubes {
+        droplet[cube] = Voxel::Lava;
+    }
+    Ok(droplet)
+}
+
+/// Number of sides of lava cubes whose neighbour, possibly out of the grid, is accepted by
+/// `is_exposed`
+fn count_sides<F>(droplet: &VoxelGrid<Voxel>, is_exposed: F) -> usize
+where
+    F: Fn(Coord3) -> bool,
+{
+    droplet
+        .iter()
+        .filter(|(_, voxel)| **voxel == Voxel::Lava)
+        .map(|(cube, _)| {
+            cube.neighbours6()
+                .filter(|neighbour| is_exposed(*neighbour))
+                .count()
+        })
+        .sum()
+}
+
+pub fn puzzle(context: &Context, lines: &[String]) -> PuzzleResult {
+    let droplet = parse(lines)?;
+    if context.is_part(Part::Part1) {
+        let result = count_sides(&droplet, |neighbour| {
+            droplet.get(neighbour) != Some(&Voxel::Lava)
+        });
+        Ok(Answer::single(result))
+    } else {
+        let outside = droplet.flood_fill_outside(|_, voxel| *voxel == Voxel::Air);
+        let trapped = droplet
+            .iter()
+            .filter(|(cube, voxel)| **voxel == Voxel::Air && !outside[*cube])
+            .count();
+        log!(debug, context, "{} air cubes trapped", trapped);
+        let result = count_sides(&droplet, |neighbour| {
+            outside.get(neighbour).copied().unwrap_or(true)
+        });
+        Ok(Answer::single(result))
+    }
+}
+
+pub fn register(registry: &mut Registry) {
+    registry.register(DayDef {
+        day: 18,
+        title: "Boiling Boulders",
+        puzzle: Puzzle::Lines(puzzle),
+        run_style: RunStyle::PerPart,
+    });
+}
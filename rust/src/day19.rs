@@ -1,11 +1,18 @@
-use regex::{Captures, Regex};
+use std::cmp::Ordering;
+
+use regex::{Captures, Match, Regex};
 use lazy_static::lazy_static;
 use crate::{
-    check_result, log,
+    answer::{Answer, PuzzleResult},
+    input::Puzzle,
+    log,
+    parse::{captures, group, offset_in, parse_group, parse_number, ParseError},
+    priority_queue::{Comparator, Cost, Key, PriorityQueue},
+    registry::{DayDef, Registry, RunStyle},
     utils::{Context, Part},
 };
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 struct PerTypeMap<T> {
     ore: T,
     clay: T,
@@ -139,30 +146,37 @@ impl Blueprint {
     }
 }
 
-fn update_requirement(req: &mut Requirements, name: &str, qty: &str) {
-    let value = qty.parse::<u16>().unwrap();
-    match name {
+fn update_requirement(req: &mut Requirements, pos: usize, offset: usize, qty: Match, name: Match) -> Result<(), ParseError> {
+    let value = parse_number::<u16>(pos, offset + qty.start(), qty.as_str())?;
+    match name.as_str() {
         "ore" => req.ore = value,
         "clay" => req.clay = value,
         "obsidian" => req.obsidian = value,
         "geode" => req.geode = value,
-        _ => panic!("Unknown name {}", name),
+        _ => return Err(ParseError::new(pos, offset + name.start(), name.as_str(), "unknown material")),
     }
+    Ok(())
 }
 
-fn update_requirements(req: &mut Requirements, parse_res: Captures) {
+/// Updates the requirements from the captures made on the text found at `offset` in line `pos`
+fn update_requirements(req: &mut Requirements, pos: usize, offset: usize, parse_res: &Captures) -> Result<(), ParseError> {
     update_requirement(
         req,
-        parse_res.name("req1name").unwrap().as_str(),
-        parse_res.name("req1qty").unwrap().as_str(),
-    );
+        pos,
+        offset,
+        group(pos, offset, parse_res, "req1qty")?,
+        group(pos, offset, parse_res, "req1name")?,
+    )?;
     if let Some(req2_name) = parse_res.name("req2name") {
         update_requirement(
             req,
-            req2_name.as_str(),
-            parse_res.name("req2qty").unwrap().as_str(),
-        );
+            pos,
+            offset,
+            group(pos, offset, parse_res, "req2qty")?,
+            req2_name,
+        )?;
     }
+    Ok(())
 }
 
 lazy_static! {
@@ -170,31 +184,27 @@ lazy_static! {
     static ref REGEXP_BLUEPRINT:Regex = Regex::new(r"^Blueprint (?P<id>\d+)$").unwrap();
 }
 
-fn parse(lines: &Vec<String>) -> Vec<Blueprint> {
+fn parse(lines: &[String]) -> Result<Vec<Blueprint>, ParseError> {
     return lines
         .iter()
-        .map(|line| {
-            let mut main_parts = line.split(": ");
-            let blueprint_descr = main_parts.nth(0).unwrap();
-            let id = REGEXP_BLUEPRINT
-                .captures(blueprint_descr)
-                .unwrap()
-                .name("id")
-                .unwrap()
-                .as_str()
-                .parse::<u16>()
-                .unwrap();
-            let requirements_str = main_parts.last().unwrap();
+        .enumerate()
+        .map(|(pos, line)| {
+            let (blueprint_descr, requirements_str) = line
+                .split_once(": ")
+                .ok_or_else(|| ParseError::line(pos, line, "expecting <Blueprint N: requirements>"))?;
+            let blueprint_parsed = captures(&REGEXP_BLUEPRINT, pos, 0, blueprint_descr, "a blueprint id")?;
+            let id = parse_group::<u16>(pos, 0, &blueprint_parsed, "id")?;
             let mut blueprint = Blueprint::init(id);
             for req_str in requirements_str.split(". ") {
-                let c = REGEXP_REQUIREMENT.captures(req_str).unwrap();
-                let name = c.name("rname").unwrap().as_str();
-                match name {
-                    "ore" => update_requirements(&mut blueprint.requirements.ore, c),
-                    "clay" => update_requirements(&mut blueprint.requirements.clay, c),
-                    "obsidian" => update_requirements(&mut blueprint.requirements.obsidian, c),
-                    "geode" => update_requirements(&mut blueprint.requirements.geode, c),
-                    _ => panic!("Unknown name {}", name),
+                let offset = offset_in(line, req_str);
+                let c = captures(&REGEXP_REQUIREMENT, pos, offset, req_str, "a robot requirement")?;
+                let name = group(pos, offset, &c, "rname")?;
+                match name.as_str() {
+                    "ore" => update_requirements(&mut blueprint.requirements.ore, pos, offset, &c)?,
+                    "clay" => update_requirements(&mut blueprint.requirements.clay, pos, offset, &c)?,
+                    "obsidian" => update_requirements(&mut blueprint.requirements.obsidian, pos, offset, &c)?,
+                    "geode" => update_requirements(&mut blueprint.requirements.geode, pos, offset, &c)?,
+                    _ => return Err(ParseError::new(pos, offset + name.start(), name.as_str(), "unknown robot")),
                 }
             }
             for iter_requirement in MaterialType::iter() {
@@ -207,7 +217,7 @@ fn parse(lines: &Vec<String>) -> Vec<Blueprint> {
                 }
             }
             blueprint.max_requirement.geode = std::u16::MAX;
-            blueprint
+            Ok(blueprint)
         })
         .collect();
 }
@@ -215,14 +225,14 @@ fn parse(lines: &Vec<String>) -> Vec<Blueprint> {
 type Stock = PerTypeMap<u16>;
 type Production = PerTypeMap<u16>;
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 struct State {
     stock: Stock,
     production: Production,
     remaining_minutes: u16,
 }
 
-struct DfsContext<'a> {
+struct SearchContext<'a> {
     blueprint: &'a Blueprint,
     best_produced: u16,
     nb_explored: u32,
@@ -291,7 +301,8 @@ struct BestEstimatedProduction {
     geodes_prod_rate: u16,
 }
 
-fn may_state_produce_more_than_best(state: &State, dfs_context: &DfsContext) -> bool {
+/// Geodes the state would end with building a geode robot every remaining minute
+fn max_geodes(state: &State) -> u16 {
     let mut best_production: BestEstimatedProduction = BestEstimatedProduction {
         geodes: state.stock.geode,
         geodes_prod_rate: state.production.geode,
@@ -303,12 +314,16 @@ fn may_state_produce_more_than_best(state: &State, dfs_context: &DfsContext) ->
         best_production.geodes_prod_rate += 1;
         remaining_minutes -= 1;
     }
-    return best_production.geodes > dfs_context.best_produced;
+    best_production.geodes
 }
 
-fn next_states(state: &State, dfs_context: &mut DfsContext) -> Vec<State> {
+fn may_state_produce_more_than_best(state: &State, search_context: &SearchContext) -> bool {
+    max_geodes(state) > search_context.best_produced
+}
+
+fn next_states(state: &State, search_context: &mut SearchContext) -> Vec<State> {
     let mut result: Vec<State> = vec![];
-    let max_req = &dfs_context.blueprint.max_requirement;
+    let max_req = &search_context.blueprint.max_requirement;
 
     for type_to_produce in MaterialType::iter_rev() {
         if type_to_produce.get(&state.production) >= type_to_produce.get(max_req) {
@@ -317,12 +332,12 @@ fn next_states(state: &State, dfs_context: &mut DfsContext) -> Vec<State> {
         if let Some(new_state) = next_state_with_production(
             state,
             &type_to_produce,
-            type_to_produce.get(&dfs_context.blueprint.requirements),
+            type_to_produce.get(&search_context.blueprint.requirements),
         ) {
-            if may_state_produce_more_than_best(&new_state, dfs_context) {
+            if may_state_produce_more_than_best(&new_state, search_context) {
                 result.push(new_state)
             } else {
-                dfs_context.nb_skipped += 1;
+                search_context.nb_skipped += 1;
             }
         }
     }
@@ -342,44 +357,43 @@ fn next_states(state: &State, dfs_context: &mut DfsContext) -> Vec<State> {
     result
 }
 
-fn explore(state: &State, dfs_context: &mut DfsContext, context: &Context, depth: u8) -> u16 {
-    log!(
-        debug,
-        context,
-        "Depth {} / Exploring {} with production {}/{}/{}/{} and stock {}/{}/{}/{} and best {}",
-        depth,
-        state.remaining_minutes,
-        state.production.ore,
-        state.production.clay,
-        state.production.obsidian,
-        state.production.geode,
-        state.stock.ore,
-        state.stock.clay,
-        state.stock.obsidian,
-        state.stock.geode,
-        dfs_context.best_produced
-    );
+/// State queued with the most geodes it could end with
+struct Candidate {
+    state: State,
+    potential: u16,
+}
 
-    dfs_context.nb_explored += 1;
-    if state.remaining_minutes == 0 {
-        return state.stock.geode;
+impl Candidate {
+    fn new(state: State) -> Candidate {
+        Candidate {
+            potential: max_geodes(&state),
+            state,
+        }
     }
+}
 
-    let mut best_produced = 0;
-    let next_states = next_states(state, dfs_context);
-    for next_state in &next_states {
-        let produced = explore(&next_state, dfs_context, context, depth + 1);
-        if produced > best_produced {
-            dfs_context.best_produced = std::cmp::max(dfs_context.best_produced, produced);
-            best_produced = produced;
-        }
+impl Cost<(u16, u16)> for Candidate {
+    fn cost(&self) -> (u16, u16) {
+        (self.potential, self.state.remaining_minutes)
     }
+}
 
-    return best_produced;
+impl Key<State> for Candidate {
+    fn key(&self) -> State {
+        self.state
+    }
 }
 
-fn maximise_dfs(max_mins: u16, blueprint: &Blueprint, context: &Context) -> u16 {
-    let mut dfs_context = DfsContext {
+/// Highest potential first, then the fewest remaining minutes to reach final states, and raise
+/// the best count, sooner
+fn by_potential_then_depth(a: &(u16, u16), b: &(u16, u16)) -> Ordering {
+    b.0.cmp(&a.0).then(a.1.cmp(&b.1))
+}
+
+/// Best-first branch and bound: once the best potential left is not above the best count
+/// reached, no other state can do better
+fn maximise(max_mins: u16, blueprint: &Blueprint, context: &Context) -> u16 {
+    let mut search_context = SearchContext {
         best_produced: 0,
         nb_explored: 0,
         nb_skipped: 0,
@@ -400,34 +414,67 @@ fn maximise_dfs(max_mins: u16, blueprint: &Blueprint, context: &Context) -> u16
             obsidian: 0,
         },
     };
-    let result = explore(&state, &mut dfs_context, context, 0);
+    let mut queue = PriorityQueue::with_order(Comparator(by_potential_then_depth));
+    queue.push(Candidate::new(state));
+    while let Some(Candidate { state, potential }) = queue.pop() {
+        if potential <= search_context.best_produced {
+            break;
+        }
+        search_context.nb_explored += 1;
+        if state.remaining_minutes == 0 {
+            search_context.best_produced = state.stock.geode;
+            log!(
+                debug,
+                context,
+                "Best {} with production {}/{}/{}/{}",
+                search_context.best_produced,
+                state.production.ore,
+                state.production.clay,
+                state.production.obsidian,
+                state.production.geode
+            );
+            continue;
+        }
+        for next_state in next_states(&state, &mut search_context) {
+            queue.push(Candidate::new(next_state));
+        }
+    }
     log!(
         debug,
         context,
         "{} with Explored {} / {}",
-        result,
-        dfs_context.nb_explored,
-        dfs_context.nb_skipped
+        search_context.best_produced,
+        search_context.nb_explored,
+        search_context.nb_skipped
     );
-    result
+    search_context.best_produced
 }
 
-pub fn puzzle(context: &Context, lines: &Vec<String>) {
-    let blueprints = parse(lines);
+pub fn puzzle(context: &Context, lines: &[String]) -> PuzzleResult {
+    let blueprints = parse(lines)?;
     if context.is_part(Part::Part1) {
-        let result = blueprints
+        let result: u16 = blueprints
             .iter()
-            .map(|blueprint| maximise_dfs(24, blueprint, context) * blueprint.id as u16)
+            .map(|blueprint| maximise(24, blueprint, context) * blueprint.id as u16)
             .sum();
 
-        check_result!(context, result, [33, 1599]);
+        Ok(Answer::single(result))
     } else {
-        let result = blueprints
+        let result: u16 = blueprints
             .iter()
             .take(3)
-            .map(|blueprint| maximise_dfs(32, blueprint, context))
+            .map(|blueprint| maximise(32, blueprint, context))
             .product();
 
-        check_result!(context, result, [3472, 14112]);
+        Ok(Answer::single(result))
     }
 }
+
+pub fn register(registry: &mut Registry) {
+    registry.register(DayDef {
+        day: 19,
+        title: "Not Enough Minerals",
+        puzzle: Puzzle::Lines(puzzle),
+        run_style: RunStyle::PerPart,
+    });
+}
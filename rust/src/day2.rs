@@ -1,4 +1,10 @@
-use crate::{check_result, utils::Context};
+use crate::{
+    answer::{Answer, PuzzleResult},
+    input::Puzzle,
+    parse::{char_at, ParseError},
+    registry::{DayDef, Registry, RunStyle},
+    utils::Context,
+};
 
 #[derive(Debug,Clone,Copy,PartialEq, Eq, PartialOrd, Ord)]
 enum Play {
@@ -14,37 +20,49 @@ enum PlayResult {
     WIN = 6,
 }
 
-fn map_play(char: char) -> Play {
+fn map_play(char: char) -> Option<Play> {
     match char {
-        'A' => Play::ROCK,
-        'B' => Play::PAPER,
-        'C' => Play::SCISSORS,
-        'X' => Play::ROCK,
-        'Y' => Play::PAPER,
-        'Z' => Play::SCISSORS,
-        _=>panic!("Shouldn't occurs")
+        'A' => Some(Play::ROCK),
+        'B' => Some(Play::PAPER),
+        'C' => Some(Play::SCISSORS),
+        'X' => Some(Play::ROCK),
+        'Y' => Some(Play::PAPER),
+        'Z' => Some(Play::SCISSORS),
+        _ => None,
     }
 }
 
-fn map_play_result(char: char) -> PlayResult {
+fn map_play_result(char: char) -> Option<PlayResult> {
     match char {
-        'X' => PlayResult::LOSS,
-        'Y' => PlayResult::DRAW,
-        'Z' => PlayResult::WIN,
-        _=>panic!("Shouldn't occurs")
+        'X' => Some(PlayResult::LOSS),
+        'Y' => Some(PlayResult::DRAW),
+        'Z' => Some(PlayResult::WIN),
+        _ => None,
     }
 }
 
-fn parse(lines: &Vec<String>) -> Vec<(Play, Play,PlayResult)> {
+fn parse_line(pos: usize, l: &str) -> Result<(Play, Play, PlayResult), ParseError> {
+    let invalid = |column: usize, char: char, message: &str| ParseError::new(pos, column, &char.to_string(), message);
+    let (other, separator, me) = (char_at(pos, l, 0)?, char_at(pos, l, 1)?, char_at(pos, l, 2)?);
+    let play = (
+        map_play(other).ok_or_else(|| invalid(0, other, "invalid play"))?,
+        map_play(me).ok_or_else(|| invalid(2, me, "invalid play"))?,
+        map_play_result(me).ok_or_else(|| invalid(2, me, "invalid play result"))?,
+    );
+    if separator != ' ' {
+        return Err(invalid(1, separator, "expecting a space"));
+    }
+    if l.len() > 3 {
+        return Err(ParseError::new(pos, 3, &l[3..], "unexpected trailing text"));
+    }
+    Ok(play)
+}
+
+fn parse(lines: &[String]) -> Result<Vec<(Play, Play,PlayResult)>, ParseError> {
     return lines
         .into_iter()
-        .map(|l| {
-            (
-                map_play(l.chars().nth(0).unwrap()),
-                map_play(l.chars().nth(2).unwrap()),
-                map_play_result(l.chars().nth(2).unwrap()),
-            )
-        })
+        .enumerate()
+        .map(|(pos, l)| parse_line(pos, l))
         .collect();
 }
 
@@ -84,9 +102,18 @@ fn get_play(other:&Play,expect:&PlayResult)->Play{
     }
 }
 
-pub fn puzzle(context: &Context, lines: &Vec<String>) {
-    let values = parse(lines);
+pub fn puzzle(_context: &Context, lines: &[String]) -> PuzzleResult {
+    let values = parse(lines)?;
     let part1:u32 = values.iter().map(|(p1,p2,_)| gain(p1,p2)).sum();
     let part2:u32 = values.iter().map(|(p1,_,expect)| gain(p1,&get_play(p1,expect))).sum();
-    check_result!(context, [part1, part2], [15, 11841, 12, 13022]);
+    Ok(Answer::pair(part1, part2))
+}
+
+pub fn register(registry: &mut Registry) {
+    registry.register(DayDef {
+        day: 2,
+        title: "Rock Paper Scissors",
+        puzzle: Puzzle::Lines(puzzle),
+        run_style: RunStyle::Simult,
+    });
 }
@@ -1,15 +1,20 @@
 use crate::{
-    check_result, log,
+    answer::{Answer, PuzzleResult},
+    input::Puzzle,
+    log,
+    parse::{parse_number, ParseError},
+    registry::{DayDef, Registry, RunStyle},
     utils::{Context, Part},
 };
 
 type CompactItem = i16;
 
-fn parse(lines: &Vec<String>) -> Vec<CompactItem> {
+fn parse(lines: &[String]) -> Result<Vec<CompactItem>, ParseError> {
     lines
         .iter()
-        .map(|line| {
-            line.parse::<i16>().unwrap()
+        .enumerate()
+        .map(|(pos, line)| {
+            parse_number(pos, 0, line)
         })
         .collect()
 }
@@ -88,21 +93,30 @@ fn calc_result(items: &Vec<CompactItem>, key: i64, context: &Context) -> i64 {
     result
 }
 
-pub fn puzzle(context: &Context, lines: &Vec<String>) {
+pub fn puzzle(context: &Context, lines: &[String]) -> PuzzleResult {
     let key = if context.is_part(Part::Part1) {
         1
     } else {
         811589153
     };
-    let mut items = parse(lines);
+    let mut items = parse(lines)?;
 
     if context.is_part(Part::Part1) {
         let mixed = mix(&mut items, key,1);
         let result = calc_result(&mixed, key, context);
-        check_result!(context, result, [3, 988]);
+        Ok(Answer::single(result))
     } else {
         let mixed = mix(&mut items, key,10);
         let result = calc_result(&mixed, key, context);
-        check_result!(context, result, [1623178306, 7768531372516]);
+        Ok(Answer::single(result))
     }
 }
+
+pub fn register(registry: &mut Registry) {
+    registry.register(DayDef {
+        day: 20,
+        title: "Grove Positioning System",
+        puzzle: Puzzle::Lines(puzzle),
+        run_style: RunStyle::PerPart,
+    });
+}
@@ -1,161 +1,141 @@
 use crate::{
-    check_result,
-    priority_queue::{Cost, Key, PriorityQueue},
+    answer::{Answer, PuzzleResult},
+    coord::{Coord, Direction},
+    grid::Grid,
+    input::Puzzle,
+    log,
+    parse::ParseError,
+    priority_queue::{IndexedPriorityQueue, Key},
+    registry::{DayDef, Registry, RunStyle},
+    search::{self, Tracking},
     utils::Context,
 };
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 enum ObjectType {
     BlizzardDown,
     BlizzardUp,
     BlizzardLeft,
     BlizzardRight,
     Empty,
+    Wall,
 }
 
-//type CellContent = ObjectType | undefined;
+/// Valley inside the walls, blizzards going through them to the opposite side
+type World = Grid<ObjectType>;
 
-struct World {
-    width: i16,
-    height: i16,
-    cells: Vec<ObjectType>,
+fn parse_cell(c: char) -> Option<ObjectType> {
+    match c {
+        '^' => Some(ObjectType::BlizzardUp),
+        'v' => Some(ObjectType::BlizzardDown),
+        '>' => Some(ObjectType::BlizzardRight),
+        '<' => Some(ObjectType::BlizzardLeft),
+        '.' => Some(ObjectType::Empty),
+        '#' => Some(ObjectType::Wall),
+        _ => None,
+    }
 }
 
-fn parse(lines: &Vec<String>) -> World {
-    let height: i16 = lines.len() as i16 - 2;
-    let width: i16 = lines.get(0).map(|line| line.len() as i16 - 2).unwrap_or(0);
-    World {
-        width,
-        height,
-        cells: lines
-            .into_iter()
-            .take(lines.len() - 1)
-            .skip(1)
-            .flat_map(|line| {
-                line.chars()
-                    .into_iter()
-                    .take(line.len() - 1)
-                    .skip(1)
-                    .map(|c| match c {
-                        '^' => ObjectType::BlizzardUp,
-                        'v' => ObjectType::BlizzardDown,
-                        '>' => ObjectType::BlizzardRight,
-                        '<' => ObjectType::BlizzardLeft,
-                        _ => ObjectType::Empty,
-                    })
-            })
-            .collect(),
+fn cell_char(cell: &ObjectType) -> char {
+    match cell {
+        ObjectType::BlizzardUp => '^',
+        ObjectType::BlizzardDown => 'v',
+        ObjectType::BlizzardRight => '>',
+        ObjectType::BlizzardLeft => '<',
+        ObjectType::Empty => '.',
+        ObjectType::Wall => '#',
     }
 }
 
-#[derive(Debug)]
-struct Coord {
-    x: i16,
-    y: i16,
+fn parse(text: &str) -> Result<World, ParseError> {
+    let map = Grid::parse(text.lines(), parse_cell)?;
+    if map.width() < 3 || map.height() < 3 {
+        return Err(ParseError::new(map.height(), 0, "", "expecting a valley surrounded by walls"));
+    }
+    Ok(map.sub_grid(Coord::new(1, 1), map.width() - 2, map.height() - 2))
 }
 
-#[derive(Debug)]
-struct State<'a> {
+#[derive(Debug, Clone)]
+struct State {
     coord: Coord,
     mins: u16,
-    target: &'a Coord,
-}
-
-static OFFSET: &'static [(i16, i16)] = &[(-1, 0), (1, 0), (0, -1), (0, 1), (0, 0)];
-
-fn calc_index(coord: &Coord, world: &World, minutes_offset: (i16, i16)) -> usize {
-    if minutes_offset.1 == 0 {
-        let mut offset = (coord.x + minutes_offset.0) % world.width;
-        if offset < 0 {
-            offset += world.width
-        }
-        (coord.y * world.width + offset) as usize
-    } else {
-        let mut offset = (coord.y  + minutes_offset.1) % world.height;
-        if offset < 0 {
-            offset += world.height
-        }
-        (offset * world.width + coord.x) as usize
-    }
 }
 
 fn is_occupied(coord: &Coord, min: &u16, world: &World) -> bool {
-    let d: i16 = *min as i16;
-    return world.cells[calc_index(coord, world, (d, 0))] == ObjectType::BlizzardLeft
-        || world.cells[calc_index(coord, world, (-d, 0))] == ObjectType::BlizzardRight
-        || world.cells[calc_index(coord, world, (0, -d))] == ObjectType::BlizzardDown
-        || world.cells[calc_index(coord, world, (0, d))] == ObjectType::BlizzardUp;
+    let d = *min as i32;
+    return *world.get_wrapping(*coord + Direction::East.offset() * d) == ObjectType::BlizzardLeft
+        || *world.get_wrapping(*coord + Direction::West.offset() * d) == ObjectType::BlizzardRight
+        || *world.get_wrapping(*coord + Direction::North.offset() * d) == ObjectType::BlizzardDown
+        || *world.get_wrapping(*coord + Direction::South.offset() * d) == ObjectType::BlizzardUp;
 }
 
-fn get_next_state<'a>(curr: &State<'a>, world: &World) -> Vec<State<'a>> {
-    OFFSET
-        .into_iter()
+fn get_next_state(curr: &State, world: &World) -> Vec<State> {
+    let width = world.width() as i32;
+    let height = world.height() as i32;
+    Direction::CARDINALS
+        .iter()
+        .map(|direction| direction.offset())
+        .chain([Coord::ZERO])
         .map(|offset| State {
-            coord: Coord {
-                x: curr.coord.x + offset.0,
-                y: curr.coord.y + offset.1,
-            },
+            coord: curr.coord + offset,
             mins: curr.mins + 1,
-            target: curr.target,
         })
         .filter(|new_state| {
             let is_start = new_state.coord.y == -1 && new_state.coord.x == 0;
-            let is_end = new_state.coord.y == world.height
-                && new_state.coord.x == (world.width - 1);
-            let is_valid_coord = (new_state.coord.x >= 0 && new_state.coord.x < world.width)
-                && (new_state.coord.y >= 0 && new_state.coord.y < world.height);
+            let is_end = new_state.coord.y == height
+                && new_state.coord.x == (width - 1);
             is_start
                 || is_end
-                || (is_valid_coord && !is_occupied(&new_state.coord, &new_state.mins, world))
+                || (world.contains(new_state.coord) && !is_occupied(&new_state.coord, &new_state.mins, world))
         })
         .collect()
 }
 
-impl<'a> Cost<u16> for State<'a> {
-    fn cost(&self) -> u16 {
-        self.mins
-            + (self.target.x - self.coord.x).abs() as u16
-            + (self.target.y - self.coord.y).abs() as u16
-    }
-}
-
-impl<'a> Key<u32> for State<'a> {
+impl Key<u32> for State {
     fn key(&self) -> u32 {
         (self.mins as u32) << 16 | (((self.coord.y + 1) as u32) << 8) | (self.coord.x + 1) as u32
     }
 }
 
-fn find_path<'a>(world: &World, start: &Coord, target: &'a Coord, start_min: &u16) -> Option<u16> {
-    let mut priority_queue: PriorityQueue<u16, u32, State<'a>> = PriorityQueue::new();
-    priority_queue.push(State {
-        coord: Coord {
-            x: start.x,
-            y: start.y,
+fn find_path(context: &Context, world: &World, start: &Coord, target: &Coord, start_min: &u16) -> Option<u16> {
+    // equal priorities popped in the order reached, for explorations reproducible when debugging
+    let queue: IndexedPriorityQueue<u16, u32, _> = IndexedPriorityQueue::new();
+    let result = search::a_star_in(
+        queue,
+        Tracking::when(context.is_debug()),
+        State {
+            coord: *start,
+            mins: *start_min,
         },
-        mins: *start_min,
-        target,
-    });
-    while let Some(next) = priority_queue.pop() {
-        if next.coord.x == target.x && next.coord.y == target.y {
-            return Option::Some(next.mins);
-        }
-        for new_state in get_next_state(&next, &world) {
-            priority_queue.push(new_state)
-        }
+        |state| get_next_state(state, world).into_iter().map(|new_state| (new_state, 1)),
+        |state| state.coord == *target,
+        |state| target.manhattan(state.coord) as u16,
+    );
+    log!(debug, context, "Search {}", result.stats);
+    if let Some(path) = result.path() {
+        let path: Vec<Coord> = path.iter().map(|state| state.coord).collect();
+        log!(debug, context, "Path from {:?} to {:?}, blizzards at minute 0\n{}", start, target, world.render_path(&path, 'E', |_, cell| cell_char(cell)));
     }
-    return Option::None;
+    result.cost().map(|mins| start_min + mins)
 }
 
-pub fn puzzle(context: &Context, lines: &Vec<String>) {
-    let world = parse(lines);
-    let start = Coord { x: 0, y: -1 };
-    let end = Coord {
-        y: world.height as i16,
-        x: world.width as i16 - 1,
-    };
+pub fn puzzle(context: &Context, text: &str) -> PuzzleResult {
+    let world = parse(text)?;
+    let start = Coord::new(0, -1);
+    let end = Coord::new(world.width() as i32 - 1, world.height() as i32);
 
-    let first_trip = find_path(&world, &start, &end, &0).unwrap();
-    let second_trip = find_path(&world, &end, &start, &first_trip).unwrap();
-    let third_trip = find_path(&world, &start, &end, &second_trip).unwrap();
-    check_result!(context, [first_trip, third_trip], [18, 292, 54, 816]);
+    let no_path = || ParseError::new(text.lines().count(), 0, "", "no path through the valley");
+    let first_trip = find_path(context, &world, &start, &end, &0).ok_or_else(no_path)?;
+    let second_trip = find_path(context, &world, &end, &start, &first_trip).ok_or_else(no_path)?;
+    let third_trip = find_path(context, &world, &start, &end, &second_trip).ok_or_else(no_path)?;
+    Ok(Answer::pair(first_trip, third_trip))
+}
+
+pub fn register(registry: &mut Registry) {
+    registry.register(DayDef {
+        day: 24,
+        title: "Blizzard Basin",
+        puzzle: Puzzle::Text(puzzle),
+        run_style: RunStyle::Simult,
+    });
 }
@@ -0,0 +1,173 @@
+use crate::{
+    answer::{Answer, PuzzleResult},
+    input::Puzzle,
+    log,
+    parse::{parse_number, ParseError},
+    registry::{DayDef, Registry, RunStyle},
+    tree::{Flow, NodeId, Tree},
+    utils::{Context, Part},
+};
+
+enum Line<'a> {
+    Cd(&'a str),
+    Ls,
+    Dir,
+    File(&'a str, u64),
+}
+
+#[derive(Debug)]
+struct Directory<'a> {
+    name: &'a str,
+    files: Vec<(&'a str, u64)>,
+    total_size: u64,
+}
+
+impl<'a> Directory<'a> {
+    fn new(name: &'a str) -> Directory<'a> {
+        Directory {
+            name,
+            files: vec![],
+            total_size: 0,
+        }
+    }
+}
+
+fn parse_line(pos: usize, line: &str) -> Result<Line<'_>, ParseError> {
+    if let Some(command) = line.strip_prefix("$ ") {
+        if command == "ls" {
+            Ok(Line::Ls)
+        } else if let Some(name) = command.strip_prefix("cd ") {
+            Ok(Line::Cd(name))
+        } else {
+            Err(ParseError::new(pos, 2, command, "unknown command"))
+        }
+    } else if line.starts_with("dir ") {
+        Ok(Line::Dir)
+    } else {
+        let (size, name) = line
+            .split_once(' ')
+            .ok_or_else(|| ParseError::line(pos, line, "expecting a file size and name"))?;
+        Ok(Line::File(name, parse_number(pos, 0, size)?))
+    }
+}
+
+fn move_dir<'a>(tree: &mut Tree<Directory<'a>>, curr: NodeId, name: &'a str) -> Option<NodeId> {
+    match name {
+        "/" => Some(tree.to_root(curr)),
+        ".." => tree.parent(curr),
+        _ => Some(
+            tree.children(curr)
+                .iter()
+                .copied()
+                .find(|child| tree[*child].name == name)
+                .unwrap_or_else(|| tree.add_child(curr, Directory::new(name))),
+        ),
+    }
+}
+
+fn add_file_if_needed<'a>(tree: &mut Tree<Directory<'a>>, curr: NodeId, name: &'a str, size: u64) {
+    if tree[curr].files.iter().any(|(file, _)| *file == name) {
+        return;
+    }
+    tree[curr].files.push((name, size));
+    tree.apply_to_root(curr, size, |dir, size| {
+        dir.total_size += size;
+        Flow::Continue(size)
+    });
+}
+
+fn parse(lines: &[String]) -> Result<Tree<Directory<'_>>, ParseError> {
+    let mut tree = Tree::new(Directory::new("/"));
+    let mut curr = tree.root();
+    for (pos, line) in lines.iter().enumerate() {
+        match parse_line(pos, line)? {
+            Line::Cd(name) => {
+                curr = move_dir(&mut tree, curr, name)
+                    .ok_or_else(|| ParseError::line(pos, line, "cannot access parent of /"))?;
+            }
+            Line::File(name, size) => add_file_if_needed(&mut tree, curr, name, size),
+            Line::Ls | Line::Dir => (),
+        }
+    }
+    Ok(tree)
+}
+
+fn display(tree: &Tree<Directory>, context: &Context) {
+    if !context.is_debug() || !context.is_test() {
+        return;
+    }
+    log!(debug, context, "{} directories", tree.len());
+    let indentation_part = "  ";
+    tree.visit(tree.root(), (), |_, dir, _, parents| {
+        let prefix = indentation_part.repeat(parents.len());
+        log!(debug, context, "{}dir {}", prefix, dir.name);
+        for (name, size) in &dir.files {
+            log!(
+                debug,
+                context,
+                "{}{}file {} {}",
+                prefix,
+                indentation_part,
+                name,
+                size
+            );
+        }
+        Flow::Continue(())
+    });
+}
+
+pub fn puzzle(context: &Context, lines: &[String]) -> PuzzleResult {
+    let tree = parse(lines)?;
+    let root = tree.root();
+    if context.is_part(Part::Part1) {
+        let max_size = 100000;
+        display(&tree, context);
+        let result: u64 = tree
+            .children(root)
+            .iter()
+            .map(|child| {
+                tree.visit(*child, 0, |_, dir, sum, _| {
+                    if dir.total_size > max_size {
+                        Flow::Continue(sum)
+                    } else {
+                        Flow::Continue(sum + dir.total_size)
+                    }
+                })
+            })
+            .sum();
+        Ok(Answer::single(result))
+    } else {
+        let total_size_available: u64 = 70000000;
+        let min_free_space: u64 = 30000000;
+        let curr_free_space = total_size_available.saturating_sub(tree[root].total_size);
+        let size_to_remove = min_free_space.saturating_sub(curr_free_space);
+
+        let result = tree
+            .visit(root, None, |_, dir, smallest: Option<u64>, _| {
+                if dir.total_size >= size_to_remove {
+                    let size = smallest.map_or(dir.total_size, |size| size.min(dir.total_size));
+                    Flow::Continue(Some(size))
+                } else {
+                    Flow::Stop(smallest)
+                }
+            })
+            .ok_or_else(|| {
+                ParseError::new(
+                    lines.len(),
+                    0,
+                    "",
+                    "no directory to remove frees enough space",
+                )
+            })?;
+        Ok(Answer::single(result))
+    }
+}
+
+pub fn register(registry: &mut Registry) {
+    registry.register(DayDef {
+        day: 7,
+        title: "No Space Left On Device",
+        puzzle: Puzzle::Lines(puzzle),
+        run_style: RunStyle::PerPart,
+    });
+}
@@ -0,0 +1,306 @@
+use std::ops::{Index, IndexMut};
+
+use rustc_hash::{FxHashMap, FxHashSet};
+
+use crate::{coord::Coord, parse::ParseError};
+
+fn invalid_char(line_index: usize, line: &[u8], x: usize) -> ParseError {
+    ParseError::new(
+        line_index,
+        x,
+        &String::from_utf8_lossy(&line[x..x + 1]),
+        "unexpected char",
+    )
+}
+
+/// Dense grid of `width` x `height` cells
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl<T> Grid<T> {
+    #[allow(dead_code)]
+    pub fn new(width: usize, height: usize, fill: T) -> Grid<T>
+    where
+        T: Clone,
+    {
+        Grid {
+            width,
+            height,
+            cells: vec![fill; width * height],
+        }
+    }
+
+    /// Grid of the chars of the lines as mapped by `mapper`, a char mapped to `None` being an error
+    /// as well as lines of different lengths
+    pub fn parse<L, F>(
+        lines: impl IntoIterator<Item = L>,
+        mut mapper: F,
+    ) -> Result<Grid<T>, ParseError>
+    where
+        L: AsRef<[u8]>,
+        F: FnMut(char) -> Option<T>,
+    {
+        let mut width = None;
+        let mut height = 0;
+        let mut cells = vec![];
+        for (line_index, line) in lines.into_iter().enumerate() {
+            let line = line.as_ref();
+            if *width.get_or_insert(line.len()) != line.len() {
+                return Err(ParseError::line(
+                    line_index,
+                    &String::from_utf8_lossy(line),
+                    format!("expecting a line of {} chars", width.unwrap_or_default()),
+                ));
+            }
+            for (x, byte) in line.iter().enumerate() {
+                cells.push(mapper(*byte as char).ok_or_else(|| invalid_char(line_index, line, x))?);
+            }
+            height += 1;
+        }
+        let width = width.ok_or_else(|| ParseError::new(0, 0, "", "empty grid"))?;
+        Ok(Grid {
+            width,
+            height,
+            cells,
+        })
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn contains(&self, pos: Coord) -> bool {
+        pos.x >= 0 && pos.y >= 0 && (pos.x as usize) < self.width && (pos.y as usize) < self.height
+    }
+
+    fn index_of(&self, pos: Coord) -> usize {
+        pos.y as usize * self.width + pos.x as usize
+    }
+
+    /// Cell at the position, `None` outside of the grid
+    #[allow(dead_code)]
+    pub fn get(&self, pos: Coord) -> Option<&T> {
+        self.contains(pos).then(|| &self.cells[self.index_of(pos)])
+    }
+
+    #[allow(dead_code)]
+    pub fn get_mut(&mut self, pos: Coord) -> Option<&mut T> {
+        if self.contains(pos) {
+            let index = self.index_of(pos);
+            Some(&mut self.cells[index])
+        } else {
+            None
+        }
+    }
+
+    /// Cell at the position, the grid repeating itself in every direction
+    pub fn get_wrapping(&self, pos: Coord) -> &T {
+        let x = pos.x.rem_euclid(self.width as i32);
+        let y = pos.y.rem_euclid(self.height as i32);
+        &self.cells[self.index_of(Coord::new(x, y))]
+    }
+
+    pub fn positions(&self) -> impl Iterator<Item = Coord> {
+        let width = self.width;
+        (0..self.cells.len())
+            .map(move |index| Coord::new((index % width) as i32, (index / width) as i32))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Coord, &T)> {
+        self.positions().zip(self.cells.iter())
+    }
+
+    /// First position, row by row, of a cell matching the predicate
+    pub fn find<P: Fn(&T) -> bool>(&self, predicate: P) -> Option<Coord> {
+        self.iter()
+            .find(|(_, cell)| predicate(cell))
+            .map(|(pos, _)| pos)
+    }
+
+    /// Orthogonal neighbours of the position inside the grid
+    pub fn neighbours4(&self, pos: Coord) -> impl Iterator<Item = Coord> + '_ {
+        pos.neighbours4()
+            .filter(|neighbour| self.contains(*neighbour))
+    }
+
+    /// Neighbours of the position inside the grid, diagonals included
+    #[allow(dead_code)]
+    pub fn neighbours8(&self, pos: Coord) -> impl Iterator<Item = Coord> + '_ {
+        pos.neighbours8()
+            .filter(|neighbour| self.contains(*neighbour))
+    }
+
+    /// Part of the grid of `width` x `height` cells starting at `origin`
+    pub fn sub_grid(&self, origin: Coord, width: usize, height: usize) -> Grid<T>
+    where
+        T: Clone,
+    {
+        let cells = (0..height as i32)
+            .flat_map(|y| (0..width as i32).map(move |x| Coord::new(origin.x + x, origin.y + y)))
+            .map(|pos| self[pos].clone())
+            .collect();
+        Grid {
+            width,
+            height,
+            cells,
+        }
+    }
+
+    /// One line per row, each cell being rendered as a char
+    pub fn render<F: Fn(Coord, &T) -> char>(&self, render_cell: F) -> String {
+        let mut result = String::with_capacity((self.width + 1) * self.height);
+        for (pos, cell) in self.iter() {
+            if pos.x == 0 && pos.y > 0 {
+                result.push('\n');
+            }
+            result.push(render_cell(pos, cell));
+        }
+        result
+    }
+
+    /// Rendering of the grid with the positions of the path shown as `mark`
+    pub fn render_path<F: Fn(Coord, &T) -> char>(
+        &self,
+        path: &[Coord],
+        mark: char,
+        render_cell: F,
+    ) -> String {
+        let on_path: FxHashSet<Coord> = path.iter().copied().collect();
+        self.render(|pos, cell| {
+            if on_path.contains(&pos) {
+                mark
+            } else {
+                render_cell(pos, cell)
+            }
+        })
+    }
+}
+
+impl<T> Index<Coord> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, pos: Coord) -> &T {
+        assert!(self.contains(pos), "{:?} outside of the grid", pos);
+        &self.cells[self.index_of(pos)]
+    }
+}
+
+impl<T> IndexMut<Coord> for Grid<T> {
+    fn index_mut(&mut self, pos: Coord) -> &mut T {
+        assert!(self.contains(pos), "{:?} outside of the grid", pos);
+        let index = self.index_of(pos);
+        &mut self.cells[index]
+    }
+}
+
+/// Unbounded grid holding only its non empty cells
+#[derive(Debug, Clone)]
+pub struct SparseGrid<T> {
+    cells: FxHashMap<Coord, T>,
+}
+
+impl<T> Default for SparseGrid<T> {
+    fn default() -> Self {
+        SparseGrid {
+            cells: FxHashMap::default(),
+        }
+    }
+}
+
+impl<T> SparseGrid<T> {
+    pub fn new() -> SparseGrid<T> {
+        SparseGrid::default()
+    }
+
+    /// Grid of the chars of the lines as mapped by `mapper`: a char mapped to `None` is an error,
+    /// one mapped to `Some(None)` is an empty cell
+    pub fn parse<L, F>(
+        lines: impl IntoIterator<Item = L>,
+        mut mapper: F,
+    ) -> Result<SparseGrid<T>, ParseError>
+    where
+        L: AsRef<[u8]>,
+        F: FnMut(char) -> Option<Option<T>>,
+    {
+        let mut grid = SparseGrid::new();
+        for (line_index, line) in lines.into_iter().enumerate() {
+            let line = line.as_ref();
+            for (x, byte) in line.iter().enumerate() {
+                let cell =
+                    mapper(*byte as char).ok_or_else(|| invalid_char(line_index, line, x))?;
+                if let Some(cell) = cell {
+                    grid.insert(Coord::new(x as i32, line_index as i32), cell);
+                }
+            }
+        }
+        Ok(grid)
+    }
+
+    pub fn insert(&mut self, pos: Coord, cell: T) -> Option<T> {
+        self.cells.insert(pos, cell)
+    }
+
+    pub fn remove(&mut self, pos: Coord) -> Option<T> {
+        self.cells.remove(&pos)
+    }
+
+    pub fn get(&self, pos: Coord) -> Option<&T> {
+        self.cells.get(&pos)
+    }
+
+    pub fn contains(&self, pos: Coord) -> bool {
+        self.cells.contains_key(&pos)
+    }
+
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    #[allow(dead_code)]
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Coord, &T)> {
+        self.cells.iter().map(|(pos, cell)| (*pos, cell))
+    }
+
+    /// Top left and bottom right corners of the smallest rectangle holding every cell
+    pub fn bounds(&self) -> Option<(Coord, Coord)> {
+        self.cells.keys().fold(None, |bounds, pos| {
+            Some(match bounds {
+                None => (*pos, *pos),
+                Some((min, max)) => (
+                    Coord::new(min.x.min(pos.x), min.y.min(pos.y)),
+                    Coord::new(max.x.max(pos.x), max.y.max(pos.y)),
+                ),
+            })
+        })
+    }
+
+    /// One line per row of the bounds, each cell being rendered as a char and empty ones as `empty`
+    pub fn render<F: Fn(Coord, &T) -> char>(&self, empty: char, render_cell: F) -> String {
+        let Some((min, max)) = self.bounds() else {
+            return String::new();
+        };
+        let mut result = String::new();
+        for y in min.y..=max.y {
+            if y > min.y {
+                result.push('\n');
+            }
+            for x in min.x..=max.x {
+                let pos = Coord::new(x, y);
+                result.push(self.get(pos).map_or(empty, |cell| render_cell(pos, cell)));
+            }
+        }
+        result
+    }
+}
@@ -0,0 +1,127 @@
+use std::time::{Duration, Instant};
+
+use crate::{answer::PuzzleResult, parse::ParseError, utils::Context};
+
+/// Block of non blank lines of an input, as separated by blank lines
+#[derive(Debug, Clone, Copy)]
+pub struct Paragraph<'a> {
+    /// Index of the first line of the paragraph in the input, starting at 0
+    pub first_line: usize,
+    pub text: &'a str,
+}
+
+impl<'a> Paragraph<'a> {
+    /// Lines of the paragraph with their index in the input
+    pub fn lines(&self) -> impl Iterator<Item = (usize, &'a str)> {
+        let first_line = self.first_line;
+        self.text
+            .lines()
+            .enumerate()
+            .map(move |(pos, line)| (first_line + pos, line))
+    }
+}
+
+/// Splits the text on blank lines, consecutive blank lines giving no empty paragraph
+pub fn paragraphs(text: &str) -> Vec<Paragraph<'_>> {
+    let mut paragraphs = vec![];
+    let mut start: Option<(usize, usize)> = None;
+    let mut offset = 0;
+    for (pos, line) in text.split_inclusive('\n').enumerate() {
+        if line.trim().is_empty() {
+            if let Some((first_line, start_offset)) = start.take() {
+                paragraphs.push(Paragraph {
+                    first_line,
+                    text: text[start_offset..offset].trim_end_matches(['\r', '\n']),
+                });
+            }
+        } else if start.is_none() {
+            start = Some((pos, offset));
+        }
+        offset += line.len();
+    }
+    if let Some((first_line, start_offset)) = start {
+        paragraphs.push(Paragraph {
+            first_line,
+            text: text[start_offset..].trim_end_matches(['\r', '\n']),
+        });
+    }
+    paragraphs
+}
+
+/// Input as text, an invalid UTF-8 sequence being reported at its position
+pub fn as_text(input: &[u8]) -> Result<&str, ParseError> {
+    std::str::from_utf8(input).map_err(|error| {
+        let valid = &input[..error.valid_up_to()];
+        let line_start = valid
+            .iter()
+            .rposition(|b| *b == b'\n')
+            .map_or(0, |pos| pos + 1);
+        let line_index = valid.iter().filter(|b| **b == b'\n').count();
+        let invalid_len = error.error_len().unwrap_or(input.len() - valid.len());
+        let invalid = &input[valid.len()..valid.len() + invalid_len];
+        ParseError::new(
+            line_index,
+            valid.len() - line_start,
+            &format!("{:02x?}", invalid),
+            "invalid UTF-8",
+        )
+    })
+}
+
+/// Lines of a raw input, without their `\n` or `\r\n` ending
+pub fn byte_lines(input: &[u8]) -> impl Iterator<Item = &[u8]> {
+    input
+        .strip_suffix(b"\n")
+        .unwrap_or(input)
+        .split(|b| *b == b'\n')
+        .map(|line| line.strip_suffix(b"\r").unwrap_or(line))
+}
+
+/// Puzzle of a day, by the form of input it consumes
+#[derive(Clone, Copy)]
+pub enum Puzzle {
+    Lines(fn(&Context, &[String]) -> PuzzleResult),
+    Text(fn(&Context, &str) -> PuzzleResult),
+    Bytes(fn(&Context, &[u8]) -> PuzzleResult),
+    Paragraphs(fn(&Context, &[Paragraph]) -> PuzzleResult),
+}
+
+/// Outcome of a puzzle call, the input preparation being timed apart from the solving
+pub struct PuzzleCall {
+    pub result: PuzzleResult,
+    pub prepare: Duration,
+    pub solve: Duration,
+}
+
+fn timed<T>(fct: impl FnOnce() -> T) -> (T, Duration) {
+    let start = Instant::now();
+    let result = fct();
+    (result, start.elapsed())
+}
+
+impl Puzzle {
+    /// Calls the puzzle once the raw input is turned into the form it consumes
+    pub fn call(&self, context: &Context, input: &[u8]) -> Result<PuzzleCall, ParseError> {
+        let start = Instant::now();
+        let (result, solve) = match self {
+            Puzzle::Bytes(fct) => timed(|| fct(context, input)),
+            Puzzle::Text(fct) => {
+                let text = as_text(input)?;
+                timed(|| fct(context, text))
+            }
+            Puzzle::Lines(fct) => {
+                let lines: Vec<String> = as_text(input)?.lines().map(String::from).collect();
+                timed(|| fct(context, &lines))
+            }
+            Puzzle::Paragraphs(fct) => {
+                let paragraphs = paragraphs(as_text(input)?);
+                timed(|| fct(context, &paragraphs))
+            }
+        };
+        Ok(PuzzleCall {
+            result,
+            prepare: start.elapsed() - solve,
+            solve,
+        })
+    }
+}
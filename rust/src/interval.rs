@@ -0,0 +1,263 @@
+use std::fmt;
+
+/// Integers from `start` to `end`, both included
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Interval {
+    pub start: i64,
+    pub end: i64,
+}
+
+impl Interval {
+    /// Interval of the bounds, given in any order
+    pub fn new(a: i64, b: i64) -> Interval {
+        Interval {
+            start: a.min(b),
+            end: a.max(b),
+        }
+    }
+
+    pub fn single(value: i64) -> Interval {
+        Interval::new(value, value)
+    }
+
+    pub fn len(&self) -> u64 {
+        self.start.abs_diff(self.end) + 1
+    }
+
+    #[allow(dead_code)]
+    pub fn contains(&self, value: i64) -> bool {
+        self.start <= value && value <= self.end
+    }
+
+    #[allow(dead_code)]
+    pub fn intersection(&self, other: &Interval) -> Option<Interval> {
+        let start = self.start.max(other.start);
+        let end = self.end.min(other.end);
+        (start <= end).then_some(Interval { start, end })
+    }
+}
+
+impl fmt::Display for Interval {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[{}..{}]", self.start, self.end)
+    }
+}
+
+/// Set of integers kept as sorted disjoint intervals, overlapping or adjacent intervals being
+/// merged
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct IntervalSet {
+    intervals: Vec<Interval>,
+}
+
+impl IntervalSet {
+    pub fn new() -> IntervalSet {
+        IntervalSet::default()
+    }
+
+    #[allow(dead_code)]
+    pub fn intervals(&self) -> &[Interval] {
+        &self.intervals
+    }
+
+    #[allow(dead_code)]
+    pub fn is_empty(&self) -> bool {
+        self.intervals.is_empty()
+    }
+
+    pub fn insert(&mut self, interval: Interval) {
+        // first interval ending at least next to the new one, then first one starting after it
+        let first = self
+            .intervals
+            .partition_point(|current| current.end < interval.start.saturating_sub(1));
+        let last = self
+            .intervals
+            .partition_point(|current| current.start <= interval.end.saturating_add(1));
+        let merged = self.intervals[first..last]
+            .iter()
+            .fold(interval, |merged, current| Interval {
+                start: merged.start.min(current.start),
+                end: merged.end.max(current.end),
+            });
+        self.intervals.splice(first..last, [merged]);
+    }
+
+    /// Adds every integer of the other set
+    #[allow(dead_code)]
+    pub fn merge(&mut self, other: &IntervalSet) {
+        for interval in &other.intervals {
+            self.insert(*interval);
+        }
+    }
+
+    pub fn subtract(&mut self, interval: Interval) {
+        let first = self
+            .intervals
+            .partition_point(|current| current.end < interval.start);
+        let last = self
+            .intervals
+            .partition_point(|current| current.start <= interval.end);
+        if first >= last {
+            return;
+        }
+        let mut remaining = vec![];
+        let (head, tail) = (self.intervals[first], self.intervals[last - 1]);
+        if head.start < interval.start {
+            remaining.push(Interval::new(head.start, interval.start - 1));
+        }
+        if tail.end > interval.end {
+            remaining.push(Interval::new(interval.end + 1, tail.end));
+        }
+        self.intervals.splice(first..last, remaining);
+    }
+
+    #[allow(dead_code)]
+    pub fn contains(&self, value: i64) -> bool {
+        let index = self
+            .intervals
+            .partition_point(|current| current.end < value);
+        self.intervals
+            .get(index)
+            .is_some_and(|current| current.contains(value))
+    }
+
+    /// Number of integers in the set
+    pub fn covered_len(&self) -> u64 {
+        self.intervals.iter().map(Interval::len).sum()
+    }
+
+    /// Intervals of the integers of `within` missing from the set
+    pub fn gaps(&self, within: Interval) -> Vec<Interval> {
+        let mut gaps = vec![];
+        let mut next = within.start;
+        for current in &self.intervals {
+            if current.end < next {
+                continue;
+            }
+            if current.start > within.end {
+                break;
+            }
+            if current.start > next {
+                gaps.push(Interval::new(next, current.start - 1));
+            }
+            next = current.end.saturating_add(1);
+        }
+        if next <= within.end {
+            gaps.push(Interval::new(next, within.end));
+        }
+        gaps
+    }
+}
+
+impl fmt::Display for IntervalSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (pos, interval) in self.intervals.iter().enumerate() {
+            if pos > 0 {
+                write!(f, " ")?;
+            }
+            write!(f, "{}", interval)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn set(intervals: &[(i64, i64)]) -> IntervalSet {
+        let mut set = IntervalSet::new();
+        for (start, end) in intervals {
+            set.insert(Interval::new(*start, *end));
+        }
+        set
+    }
+
+    fn intervals(pairs: &[(i64, i64)]) -> Vec<Interval> {
+        pairs
+            .iter()
+            .map(|(start, end)| Interval::new(*start, *end))
+            .collect()
+    }
+
+    #[test]
+    fn merges_adjacent_intervals() {
+        let covered = set(&[(5, 7), (1, 2), (3, 4)]);
+        assert_eq!(covered.intervals(), intervals(&[(1, 7)]));
+        assert_eq!(covered.covered_len(), 7);
+    }
+
+    #[test]
+    fn merges_overlapping_intervals() {
+        let mut covered = set(&[(0, 3), (10, 12), (20, 25)]);
+        covered.insert(Interval::new(2, 11));
+        assert_eq!(covered.intervals(), intervals(&[(0, 12), (20, 25)]));
+        covered.insert(Interval::new(-5, 30));
+        assert_eq!(covered.intervals(), intervals(&[(-5, 30)]));
+    }
+
+    #[test]
+    fn keeps_separated_intervals_apart() {
+        let covered = set(&[(6, 8), (0, 1), (3, 4)]);
+        assert_eq!(covered.intervals(), intervals(&[(0, 1), (3, 4), (6, 8)]));
+        assert!(covered.contains(3) && !covered.contains(2) && !covered.contains(9));
+        assert_eq!(covered.to_string(), "[0..1] [3..4] [6..8]");
+    }
+
+    #[test]
+    fn subtracts_from_the_middle() {
+        let mut covered = set(&[(0, 10)]);
+        covered.subtract(Interval::single(5));
+        assert_eq!(covered.intervals(), intervals(&[(0, 4), (6, 10)]));
+        covered.subtract(Interval::new(3, 7));
+        assert_eq!(covered.intervals(), intervals(&[(0, 2), (8, 10)]));
+        assert_eq!(covered.covered_len(), 6);
+    }
+
+    #[test]
+    fn subtracts_across_and_outside_intervals() {
+        let mut covered = set(&[(0, 2), (5, 7), (10, 12)]);
+        covered.subtract(Interval::new(20, 30));
+        assert_eq!(covered.intervals(), intervals(&[(0, 2), (5, 7), (10, 12)]));
+        covered.subtract(Interval::new(1, 11));
+        assert_eq!(covered.intervals(), intervals(&[(0, 0), (12, 12)]));
+        covered.subtract(Interval::new(-1, 13));
+        assert!(covered.is_empty());
+    }
+
+    #[test]
+    fn finds_the_gaps_inside_and_at_the_edges_of_the_range() {
+        let covered = set(&[(2, 3), (6, 7)]);
+        assert_eq!(
+            covered.gaps(Interval::new(0, 9)),
+            intervals(&[(0, 1), (4, 5), (8, 9)])
+        );
+    }
+
+    #[test]
+    fn finds_no_gap_at_covered_range_edges() {
+        let covered = set(&[(-5, 3), (6, 20)]);
+        assert_eq!(covered.gaps(Interval::new(0, 10)), intervals(&[(4, 5)]));
+        assert_eq!(covered.gaps(Interval::new(4, 5)), intervals(&[(4, 5)]));
+        assert_eq!(covered.gaps(Interval::new(-5, 3)), vec![]);
+        assert_eq!(
+            IntervalSet::new().gaps(Interval::new(1, 2)),
+            intervals(&[(1, 2)])
+        );
+    }
+
+    #[test]
+    fn merges_sets_and_intersects_intervals() {
+        let mut covered = set(&[(0, 2)]);
+        covered.merge(&set(&[(3, 5), (8, 9)]));
+        assert_eq!(covered.intervals(), intervals(&[(0, 5), (8, 9)]));
+        let interval = Interval::new(4, 0);
+        assert_eq!(interval, Interval { start: 0, end: 4 });
+        assert_eq!(interval.len(), 5);
+        assert_eq!(
+            interval.intersection(&Interval::new(3, 8)),
+            Some(Interval::new(3, 4))
+        );
+        assert_eq!(interval.intersection(&Interval::new(5, 8)), None);
+    }
+}
@@ -1,34 +1,129 @@
 use std::time::Instant;
 
-use crate::utils::{RunOption, DaysRestriction};
+use crate::{
+    bench::Baseline,
+    cli::{CliOptions, Command},
+    registry::Registry,
+    utils::print_human,
+};
 
+mod answer;
+mod bench;
+mod cli;
+mod coord;
+mod cycle;
 mod day1;
 mod day2;
+mod day7;
 mod day11;
 mod day12;
+mod day13;
+mod day15;
 mod day16;
+mod day17;
+mod day18;
 mod day19;
 mod day20;
 mod day23;
 mod day24;
-mod utils;
+mod grid;
+mod input;
+mod interval;
+mod parse;
+mod pool;
 mod priority_queue;
+mod registry;
+mod report;
+mod search;
+mod tree;
+mod utils;
+mod voxel;
+
+fn build_registry() -> Registry {
+    let mut registry = Registry::new();
+    day1::register(&mut registry);
+    day2::register(&mut registry);
+    day7::register(&mut registry);
+    day11::register(&mut registry);
+    day12::register(&mut registry);
+    day13::register(&mut registry);
+    day15::register(&mut registry);
+    day16::register(&mut registry);
+    day17::register(&mut registry);
+    day18::register(&mut registry);
+    day19::register(&mut registry);
+    day20::register(&mut registry);
+    day23::register(&mut registry);
+    day24::register(&mut registry);
+    registry
+}
 
 fn main() {
+    let registry = build_registry();
+    let cli_options: Box<CliOptions> = match cli::parse_args(std::env::args().skip(1)) {
+        Ok(Command::Run(cli_options)) => cli_options,
+        Ok(Command::List) => {
+            for day_def in registry.days() {
+                println!("Day {:>2} [{}] {}", day_def.day, day_def.run_style, day_def.title);
+            }
+            return;
+        }
+        Ok(Command::Help) => {
+            println!("{}", cli::usage());
+            return;
+        }
+        Err(error) => {
+            eprintln!("{}", error);
+            eprintln!();
+            eprintln!("{}", cli::usage());
+            std::process::exit(2);
+        }
+    };
+    if let Some(days) = cli_options.days_restriction() {
+        for day in days.iter().filter(|day| registry.get(**day).is_none()) {
+            eprintln!("[Day {}] not available", day);
+        }
+    }
+    // Runs abandoned after a timeout keep a copy of the options, which must then live until the end
+    let cli_options: &'static CliOptions = Box::leak(cli_options);
+    let options = cli_options.run_option();
+    utils::install_panic_hook();
+
     let start = Instant::now();
-    //let days_restriction:DaysRestriction = &Some(vec![20]);
-    let days_restriction:DaysRestriction = &None;
-    utils::run_all(&1, &day1::puzzle, RunOption::default(days_restriction));
-    utils::run_all_simult(&2, &day2::puzzle, RunOption::default(days_restriction));
-    utils::run_all(&11, &day11::puzzle, RunOption::default(days_restriction));
-    utils::run_all(&12, &day12::puzzle,RunOption::default(days_restriction));
-    utils::run_all_simult(&16, &day16::puzzle,RunOption::default(days_restriction));
-    utils::run_all(&19, &day19::puzzle,RunOption::default(days_restriction));
-    utils::run_all(&20, &day20::puzzle,RunOption::default(days_restriction));
-    utils::run_all(&23, &day23::puzzle,RunOption::default(days_restriction));
-    utils::run_all_simult(&24, &day24::puzzle,RunOption::default(days_restriction));
+    let summary = registry.run_all(&options);
     let duration = start.elapsed().as_millis() as u64;
-    println!("");
-    println!("[ALL] Overall finished in {} ms",duration);
+    let output = options.get_format();
+    print_human(output, "");
+    summary.print(output);
+    if options.is_bench() {
+        print_human(output, "");
+        summary.print_bench(output);
+    }
+    let mut nb_regressions = 0;
+    if let Some(filename) = cli_options.baseline() {
+        print_human(output, "");
+        match Baseline::load(filename) {
+            Ok(baseline) => {
+                nb_regressions = baseline.compare(summary.reports(), cli_options.regression_threshold(), output)
+            }
+            Err(error) => print_human(output, &format!("[ALL] {}", error)),
+        }
+    }
+    if let Some(filename) = cli_options.save_baseline() {
+        match Baseline::save(filename, summary.reports()) {
+            Ok(()) => print_human(output, &format!("[ALL] Baseline saved to {}", filename)),
+            Err(error) => print_human(output, &format!("[ALL] {}", error)),
+        }
+    }
+    print_human(
+        output,
+        &format!(
+            "[ALL] Overall finished in {} ms ({} ms of summed run time)",
+            duration,
+            summary.total_elapsed().as_millis()
+        ),
+    );
+    if summary.has_failure() || nb_regressions > 0 {
+        std::process::exit(1);
+    }
 }
-
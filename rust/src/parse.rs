@@ -0,0 +1,120 @@
+use std::{fmt, str::FromStr};
+
+use regex::{Captures, Match, Regex};
+
+/// Error raised by a day parser on an input it can't read
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    /// Day of the input, filled by the runner
+    pub day: Option<u8>,
+    /// Line of the input, starting at 1
+    pub line: usize,
+    /// Column in the line, starting at 1
+    pub column: usize,
+    /// Offending text
+    pub text: String,
+    pub message: String,
+}
+
+impl ParseError {
+    /// Error on the text found at the 0-based `line_index` and `offset` of the input
+    pub fn new<M: Into<String>>(
+        line_index: usize,
+        offset: usize,
+        text: &str,
+        message: M,
+    ) -> ParseError {
+        ParseError {
+            day: None,
+            line: line_index + 1,
+            column: offset + 1,
+            text: text.to_string(),
+            message: message.into(),
+        }
+    }
+
+    /// Error on a whole line
+    pub fn line<M: Into<String>>(line_index: usize, line: &str, message: M) -> ParseError {
+        ParseError::new(line_index, 0, line, message)
+    }
+
+    pub fn with_day(self, day: u8) -> ParseError {
+        ParseError {
+            day: Some(day),
+            ..self
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(day) = self.day {
+            write!(f, "day {} ", day)?;
+        }
+        write!(
+            f,
+            "line {} column {}: {} <{}>",
+            self.line, self.column, self.message, self.text
+        )
+    }
+}
+
+/// Offset of `part` in `line`, `part` having to be a slice of `line`
+pub fn offset_in(line: &str, part: &str) -> usize {
+    part.as_ptr() as usize - line.as_ptr() as usize
+}
+
+pub fn parse_number<T: FromStr>(
+    line_index: usize,
+    offset: usize,
+    text: &str,
+) -> Result<T, ParseError> {
+    text.parse::<T>()
+        .map_err(|_| ParseError::new(line_index, offset, text, "invalid number"))
+}
+
+pub fn char_at(line_index: usize, line: &str, offset: usize) -> Result<char, ParseError> {
+    line.get(offset..)
+        .and_then(|rest| rest.chars().next())
+        .ok_or_else(|| ParseError::new(line_index, offset, "", "missing character"))
+}
+
+/// Captures of the regex on the whole `text`, found at `offset` in the line
+pub fn captures<'t>(
+    regex: &Regex,
+    line_index: usize,
+    offset: usize,
+    text: &'t str,
+    expecting: &str,
+) -> Result<Captures<'t>, ParseError> {
+    regex.captures(text).ok_or_else(|| {
+        ParseError::new(line_index, offset, text, format!("expecting {}", expecting))
+    })
+}
+
+/// Named group of captures made on the text found at `offset` in the line
+pub fn group<'t>(
+    line_index: usize,
+    offset: usize,
+    captures: &Captures<'t>,
+    name: &str,
+) -> Result<Match<'t>, ParseError> {
+    captures.name(name).ok_or_else(|| {
+        ParseError::new(
+            line_index,
+            offset,
+            &captures[0],
+            format!("missing {}", name),
+        )
+    })
+}
+
+pub fn parse_group<T: FromStr>(
+    line_index: usize,
+    offset: usize,
+    captures: &Captures,
+    name: &str,
+) -> Result<T, ParseError> {
+    let found = group(line_index, offset, captures, name)?;
+    parse_number(line_index, offset + found.start(), found.as_str())
+}
@@ -0,0 +1,39 @@
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        mpsc,
+    },
+    thread,
+};
+
+/// Runs `fct` on every item with `nb_threads` worker threads, each worker taking the next pending item
+/// when it is done with the previous one. The results are returned in the items order.
+pub fn run_parallel<T, R, F>(items: &[T], nb_threads: usize, fct: F) -> Vec<R>
+where
+    T: Sync,
+    R: Send,
+    F: Fn(&T) -> R + Sync,
+{
+    let next_item = AtomicUsize::new(0);
+    let (sender, receiver) = mpsc::channel();
+    thread::scope(|scope| {
+        for _ in 0..nb_threads.clamp(1, items.len().max(1)) {
+            let sender = sender.clone();
+            let (next_item, fct) = (&next_item, &fct);
+            scope.spawn(move || loop {
+                let pos = next_item.fetch_add(1, Ordering::SeqCst);
+                if pos >= items.len() {
+                    break;
+                }
+                sender.send((pos, fct(&items[pos]))).unwrap();
+            });
+        }
+    });
+    drop(sender);
+
+    let mut results: Vec<Option<R>> = (0..items.len()).map(|_| None).collect();
+    for (pos, result) in receiver {
+        results[pos] = Some(result);
+    }
+    results.into_iter().map(|r| r.unwrap()).collect()
+}
@@ -1,7 +1,4 @@
-use std::{
-    collections::{BinaryHeap},
-    hash::Hash,
-};
+use std::{cmp::Ordering, collections::hash_map::Entry as HashEntry, hash::Hash};
 
 use rustc_hash::FxHashMap;
 
@@ -13,70 +10,109 @@ pub trait Key<K> {
     fn key(&self) -> K;
 }
 
-#[derive(Debug)]
-struct Wrapper<C: Ord + PartialOrd, K, T> {
-    item: T,
-    key: K,
-    cost: C,
+/// Ordering policy of a queue, the cost ordered first being popped first and being the one kept
+/// when a key is pushed several times
+pub trait Order<C> {
+    fn compare(&self, a: &C, b: &C) -> Ordering;
 }
 
-impl<C: Ord + PartialOrd, K, T> PartialEq for Wrapper<C, K, T> {
-    fn eq(&self, other: &Self) -> bool {
-        self.cost == other.cost
+/// Lowest cost first
+#[derive(Debug, Default, Clone, Copy)]
+pub struct MinFirst;
+
+/// Highest cost first, as when exploring the most promising states first
+#[derive(Debug, Default, Clone, Copy)]
+pub struct MaxFirst;
+
+/// Custom ordering, `Ordering::Less` meaning the first cost comes first
+#[derive(Debug, Clone, Copy)]
+pub struct Comparator<F>(pub F);
+
+impl<C: Ord> Order<C> for MinFirst {
+    fn compare(&self, a: &C, b: &C) -> Ordering {
+        a.cmp(b)
     }
 }
-impl<C: Ord + PartialOrd, K, T> Eq for Wrapper<C, K, T> {}
 
-impl<C: Ord + PartialOrd, K, T> Ord for Wrapper<C, K, T> {
-    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        other.cost.cmp(&self.cost)
+impl<C: Ord> Order<C> for MaxFirst {
+    fn compare(&self, a: &C, b: &C) -> Ordering {
+        b.cmp(a)
     }
 }
 
-impl<C: Ord + PartialOrd, K, T> PartialOrd for Wrapper<C, K, T> {
-    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        other.cost.partial_cmp(&self.cost)
+impl<C, F: Fn(&C, &C) -> Ordering> Order<C> for Comparator<F> {
+    fn compare(&self, a: &C, b: &C) -> Ordering {
+        (self.0)(a, b)
     }
 }
 
-pub struct PriorityQueue<C: Ord + Copy + Clone, K: Hash + Copy + Clone, T: Cost<C> + Key<K>> {
+#[derive(Debug)]
+struct Wrapper<C, K, T> {
+    item: T,
+    key: K,
+    cost: C,
+}
+
+/// Priority queue skipping the items of a key already pushed with a cost ordered before or as
+/// theirs, ordered by `O`, the lowest cost first by default
+pub struct PriorityQueue<C: Copy, K: Hash + Copy, T: Cost<C> + Key<K>, O: Order<C> = MinFirst> {
     best_inserted: FxHashMap<K, C>,
-    queue: BinaryHeap<Wrapper<C,K, T>>,
+    queue: Vec<Wrapper<C, K, T>>,
+    order: O,
 }
 
-impl<C: Ord + Copy + Clone, K: Hash + Eq + Copy + Clone, T: Cost<C> + Key<K>>
-    PriorityQueue<C, K, T>
-{
-    pub fn new() -> PriorityQueue<C, K, T> {
-        return PriorityQueue {
-            queue: BinaryHeap::new(),
+impl<C: Copy, K: Hash + Eq + Copy, T: Cost<C> + Key<K>, O: Order<C>> PriorityQueue<C, K, T, O> {
+    pub fn new() -> PriorityQueue<C, K, T, O>
+    where
+        O: Default,
+    {
+        PriorityQueue::with_order(O::default())
+    }
+
+    pub fn with_order(order: O) -> PriorityQueue<C, K, T, O> {
+        PriorityQueue {
+            queue: vec![],
             best_inserted: FxHashMap::default(),
-        };
+            order,
+        }
+    }
+
+    /// Whether `a` is ordered strictly before `b`
+    fn is_before(&self, a: &C, b: &C) -> bool {
+        self.order.compare(a, b) == Ordering::Less
     }
 
-    pub fn push(&mut self, v: T) {
+    /// Queues the item unless its key was already pushed with a cost ordered before or as its
+    /// one, telling whether it was queued
+    pub fn push(&mut self, v: T) -> bool {
         let cost = v.cost();
         let key: K = v.key();
         if self
             .best_inserted
             .get(&key)
-            .filter(|c| **c <= cost)
+            .filter(|c| !self.is_before(&cost, c))
             .is_some()
         {
-            return;
+            return false;
         }
         let wrapper = Wrapper { item: v, key, cost };
 
         self.best_inserted.insert(key, cost);
         self.queue.push(wrapper);
+        self.sift_up(self.queue.len() - 1);
+        true
     }
 
     pub fn pop(&mut self) -> Option<T> {
-        while let Some(w) = self.queue.pop(){
+        while !self.queue.is_empty() {
+            let w = self.queue.swap_remove(0);
+            if !self.queue.is_empty() {
+                self.sift_down(0);
+            }
             if self
                 .best_inserted
                 .get(&w.key)
-                .filter(|c| **c < w.cost)
+                .filter(|c| self.is_before(c, &w.cost))
                 .is_none()
             {
                 return Some(w.item);
@@ -84,4 +120,351 @@ impl<C: Ord + Copy + Clone, K: Hash + Eq + Copy + Clone, T: Cost<C> + Key<K>>
         }
         None
     }
+
+    fn sift_up(&mut self, mut index: usize) {
+        while index > 0 {
+            let parent = (index - 1) / 2;
+            if !self.is_before(&self.queue[index].cost, &self.queue[parent].cost) {
+                break;
+            }
+            self.queue.swap(index, parent);
+            index = parent;
+        }
+    }
+
+    fn sift_down(&mut self, mut index: usize) {
+        loop {
+            let mut first = index;
+            for child in [2 * index + 1, 2 * index + 2] {
+                if child < self.queue.len()
+                    && self.is_before(&self.queue[child].cost, &self.queue[first].cost)
+                {
+                    first = child;
+                }
+            }
+            if first == index {
+                break;
+            }
+            self.queue.swap(index, first);
+            index = first;
+        }
+    }
+}
+
+/// Heap entry of a queued item, the item itself staying in the slot of its key
+#[derive(Clone, Copy)]
+struct Entry<C> {
+    cost: C,
+    /// Insertion order, breaking ties between equal costs
+    seq: u64,
+    /// Slot of the key of the item
+    slot: usize,
+}
+
+struct KeyState<C> {
+    best: C,
+    slot: usize,
+}
+
+struct Slot<T> {
+    /// Item of the key, while it is queued
+    item: Option<T>,
+    /// Index in the heap of the item, while it is queued
+    index: usize,
+}
+
+/// Priority queue holding at most one item per key: pushing a queued key with a cost ordered
+/// before its current one updates it in place and items of equal costs are popped in insertion
+/// order
+pub struct IndexedPriorityQueue<
+    C: Copy,
+    K: Hash + Eq + Copy,
+    T: Cost<C> + Key<K>,
+    O: Order<C> = MinFirst,
+> {
+    heap: Vec<Entry<C>>,
+    keys: FxHashMap<K, KeyState<C>>,
+    slots: Vec<Slot<T>>,
+    next_seq: u64,
+    order: O,
+}
+
+impl<C: Copy, K: Hash + Eq + Copy, T: Cost<C> + Key<K>, O: Order<C>>
+    IndexedPriorityQueue<C, K, T, O>
+{
+    pub fn new() -> IndexedPriorityQueue<C, K, T, O>
+    where
+        O: Default,
+    {
+        IndexedPriorityQueue::with_order(O::default())
+    }
+
+    pub fn with_order(order: O) -> IndexedPriorityQueue<C, K, T, O> {
+        IndexedPriorityQueue {
+            heap: vec![],
+            keys: FxHashMap::default(),
+            slots: vec![],
+            next_seq: 0,
+            order,
+        }
+    }
+
+    /// Whether `a` is popped before `b`
+    fn is_before(&self, a: &Entry<C>, b: &Entry<C>) -> bool {
+        self.order.compare(&a.cost, &b.cost).then(a.seq.cmp(&b.seq)) == Ordering::Less
+    }
+
+    #[allow(dead_code)]
+    pub fn len(&self) -> usize {
+        self.heap.len()
+    }
+
+    #[allow(dead_code)]
+    pub fn is_empty(&self) -> bool {
+        self.heap.is_empty()
+    }
+
+    /// Next item to be popped
+    #[allow(dead_code)]
+    pub fn peek(&self) -> Option<&T> {
+        self.heap
+            .first()
+            .and_then(|entry| self.slots[entry.slot].item.as_ref())
+    }
+
+    /// Queues the item unless its key was already pushed with a cost ordered before or as its
+    /// one, telling whether it was queued
+    pub fn push(&mut self, v: T) -> bool {
+        let cost = v.cost();
+        let slot = match self.keys.entry(v.key()) {
+            HashEntry::Occupied(mut state) => {
+                let state = state.get_mut();
+                if self.order.compare(&cost, &state.best) != Ordering::Less {
+                    return false;
+                }
+                state.best = cost;
+                state.slot
+            }
+            HashEntry::Vacant(state) => {
+                let slot = self.slots.len();
+                self.slots.push(Slot {
+                    item: None,
+                    index: 0,
+                });
+                state.insert(KeyState { best: cost, slot });
+                slot
+            }
+        };
+        let entry = Entry {
+            cost,
+            seq: self.next_seq,
+            slot,
+        };
+        self.next_seq += 1;
+        // a better cost only moves the item towards the top
+        let index = if self.slots[slot].item.replace(v).is_some() {
+            self.slots[slot].index
+        } else {
+            self.heap.push(entry);
+            self.heap.len() - 1
+        };
+        self.sift_up(index, entry);
+        true
+    }
+
+    pub fn pop(&mut self) -> Option<T> {
+        if self.heap.is_empty() {
+            return None;
+        }
+        let top = self.heap.swap_remove(0);
+        if let Some(&last) = self.heap.first() {
+            self.sift_down(0, last);
+        }
+        self.slots[top.slot].item.take()
+    }
+
+    /// Puts the entry at `index` in the heap
+    fn place(&mut self, index: usize, entry: Entry<C>) {
+        self.heap[index] = entry;
+        self.slots[entry.slot].index = index;
+    }
+
+    /// Moves the entry up from `index`, the parents ordered after it moving down to the hole
+    fn sift_up(&mut self, mut index: usize, entry: Entry<C>) {
+        while index > 0 {
+            let parent = (index - 1) / 2;
+            if !self.is_before(&entry, &self.heap[parent]) {
+                break;
+            }
+            self.place(index, self.heap[parent]);
+            index = parent;
+        }
+        self.place(index, entry);
+    }
+
+    /// Moves the entry down from `index`, the children ordered before it moving up to the hole
+    fn sift_down(&mut self, mut index: usize, entry: Entry<C>) {
+        loop {
+            let mut child = 2 * index + 1;
+            if child >= self.heap.len() {
+                break;
+            }
+            if child + 1 < self.heap.len()
+                && self.is_before(&self.heap[child + 1], &self.heap[child])
+            {
+                child += 1;
+            }
+            if !self.is_before(&self.heap[child], &entry) {
+                break;
+            }
+            self.place(index, self.heap[child]);
+            index = child;
+        }
+        self.place(index, entry);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    struct Item {
+        name: char,
+        cost: u32,
+    }
+
+    impl Cost<u32> for Item {
+        fn cost(&self) -> u32 {
+            self.cost
+        }
+    }
+
+    impl Key<char> for Item {
+        fn key(&self) -> char {
+            self.name
+        }
+    }
+
+    fn item(name: char, cost: u32) -> Item {
+        Item { name, cost }
+    }
+
+    fn drain<O: Order<u32>>(queue: &mut IndexedPriorityQueue<u32, char, Item, O>) -> Vec<Item> {
+        std::iter::from_fn(|| queue.pop()).collect()
+    }
+
+    #[test]
+    fn pops_lowest_cost_first() {
+        let mut queue = IndexedPriorityQueue::<u32, char, Item>::new();
+        assert!(queue.is_empty());
+        for (name, cost) in [('a', 5), ('b', 1), ('c', 3)] {
+            assert!(queue.push(item(name, cost)));
+        }
+        assert_eq!(queue.len(), 3);
+        assert_eq!(queue.peek(), Some(&item('b', 1)));
+        assert_eq!(
+            drain(&mut queue),
+            [item('b', 1), item('c', 3), item('a', 5)]
+        );
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn decreases_the_cost_of_a_queued_key() {
+        let mut queue = IndexedPriorityQueue::<u32, char, Item>::new();
+        queue.push(item('a', 2));
+        queue.push(item('b', 8));
+        assert!(queue.push(item('b', 1)));
+        assert_eq!(queue.len(), 2);
+        assert_eq!(drain(&mut queue), [item('b', 1), item('a', 2)]);
+    }
+
+    #[test]
+    fn rejects_a_cost_not_better_than_the_pushed_one() {
+        let mut queue = IndexedPriorityQueue::<u32, char, Item>::new();
+        queue.push(item('a', 2));
+        assert!(!queue.push(item('a', 3)));
+        assert!(!queue.push(item('a', 2)));
+        assert_eq!(drain(&mut queue), [item('a', 2)]);
+    }
+
+    #[test]
+    fn reopens_a_popped_key_only_with_a_better_cost() {
+        let mut queue = IndexedPriorityQueue::<u32, char, Item>::new();
+        queue.push(item('a', 4));
+        assert_eq!(queue.pop(), Some(item('a', 4)));
+        assert!(!queue.push(item('a', 4)));
+        assert!(queue.is_empty());
+        assert!(queue.push(item('a', 3)));
+        assert_eq!(drain(&mut queue), [item('a', 3)]);
+    }
+
+    #[test]
+    fn pops_equal_costs_in_insertion_order() {
+        let mut queue = IndexedPriorityQueue::<u32, char, Item>::new();
+        for name in ['d', 'a', 'c', 'b'] {
+            queue.push(item(name, 1));
+        }
+        queue.push(item('e', 0));
+        let names: String = drain(&mut queue).iter().map(|i| i.name).collect();
+        assert_eq!(names, "edacb");
+    }
+
+    #[test]
+    fn decreased_key_comes_after_earlier_items_of_its_new_cost() {
+        let mut queue = IndexedPriorityQueue::<u32, char, Item>::new();
+        queue.push(item('a', 5));
+        queue.push(item('b', 1));
+        queue.push(item('a', 1));
+        let names: String = drain(&mut queue).iter().map(|i| i.name).collect();
+        assert_eq!(names, "ba");
+    }
+
+    #[test]
+    fn max_first_pops_highest_cost_first() {
+        let mut queue = IndexedPriorityQueue::<u32, char, Item, MaxFirst>::new();
+        for (name, cost) in [('a', 5), ('b', 1), ('c', 3)] {
+            queue.push(item(name, cost));
+        }
+        assert_eq!(
+            drain(&mut queue),
+            [item('a', 5), item('c', 3), item('b', 1)]
+        );
+    }
+
+    #[test]
+    fn max_first_keeps_the_highest_cost_of_a_key() {
+        let mut queue = IndexedPriorityQueue::<u32, char, Item, MaxFirst>::new();
+        queue.push(item('a', 3));
+        queue.push(item('b', 4));
+        assert!(!queue.push(item('a', 1)));
+        assert!(queue.push(item('a', 7)));
+        assert_eq!(drain(&mut queue), [item('a', 7), item('b', 4)]);
+        assert!(!queue.push(item('a', 6)));
+        assert!(queue.push(item('a', 8)));
+    }
+
+    #[test]
+    fn comparator_orders_by_the_given_function() {
+        // even costs first, then the lowest
+        let order = Comparator(|a: &u32, b: &u32| (a % 2).cmp(&(b % 2)).then(a.cmp(b)));
+        let mut queue = IndexedPriorityQueue::with_order(order);
+        for (name, cost) in [('a', 3), ('b', 4), ('c', 1), ('d', 2)] {
+            queue.push(item(name, cost));
+        }
+        let names: String = drain(&mut queue).iter().map(|i| i.name).collect();
+        assert_eq!(names, "dbca");
+    }
+
+    #[test]
+    fn comparator_keeps_the_cost_of_a_key_ordered_first() {
+        let order = Comparator(|a: &u32, b: &u32| (a % 2).cmp(&(b % 2)).then(a.cmp(b)));
+        let mut queue = IndexedPriorityQueue::with_order(order);
+        queue.push(item('a', 1));
+        queue.push(item('b', 3));
+        assert!(!queue.push(item('a', 5)));
+        assert!(queue.push(item('a', 10)));
+        assert_eq!(drain(&mut queue), [item('a', 10), item('b', 3)]);
+    }
 }
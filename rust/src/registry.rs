@@ -0,0 +1,164 @@
+use std::{fmt, sync::Arc};
+
+use crate::{
+    answer::ExpectedAnswers,
+    input::Puzzle,
+    pool,
+    report::{RunReport, Summary},
+    utils::{self, Context, Dataset, OutputLine, Part, RunOption},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RunStyle {
+    /// The puzzle is called once per part, the part being given by the context
+    PerPart,
+    /// The puzzle computes both parts in a single call
+    Simult,
+}
+
+impl fmt::Display for RunStyle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RunStyle::PerPart => write!(f, "per part"),
+            RunStyle::Simult => write!(f, "simult"),
+        }
+    }
+}
+
+pub struct DayDef {
+    pub day: u8,
+    pub title: &'static str,
+    pub puzzle: Puzzle,
+    pub run_style: RunStyle,
+}
+
+impl DayDef {
+    pub fn run(&self, options: &RunOption<'static>) -> Vec<RunReport> {
+        match self.run_style {
+            RunStyle::PerPart => utils::run_all(&self.day, self.puzzle, options),
+            RunStyle::Simult => utils::run_all_simult(&self.day, self.puzzle, options),
+        }
+    }
+
+    fn run_type(&self) -> &'static str {
+        match self.run_style {
+            RunStyle::PerPart => "run per part",
+            RunStyle::Simult => "run global",
+        }
+    }
+
+    /// Part (`None` for a simult run) and dataset of each run of the day allowed by the options
+    fn jobs(&self, options: &RunOption) -> Vec<(Option<Part>, Dataset)> {
+        let parts = match self.run_style {
+            RunStyle::PerPart => utils::PER_PART_RUNS,
+            RunStyle::Simult => utils::SIMULT_RUNS,
+        };
+        utils::day_runs(&self.day, parts, options)
+    }
+}
+
+struct Job<'a> {
+    day_def: &'a DayDef,
+    expected: &'a Arc<ExpectedAnswers>,
+    part: Option<Part>,
+    data_set: Dataset,
+}
+
+impl<'a> Job<'a> {
+    fn run(&self, options: &RunOption<'static>) -> (RunReport, Vec<OutputLine>) {
+        let day_def = self.day_def;
+        let context = Context::new_buffered(&day_def.day, options, self.part, &self.data_set);
+        let report = utils::run(&context, day_def.puzzle, self.expected, options);
+        (report, context.take_output())
+    }
+}
+
+#[derive(Default)]
+pub struct Registry {
+    days: Vec<DayDef>,
+}
+
+impl Registry {
+    pub fn new() -> Registry {
+        Registry::default()
+    }
+
+    pub fn register(&mut self, day_def: DayDef) {
+        match self.days.binary_search_by_key(&day_def.day, |d| d.day) {
+            Ok(_) => panic!("Day {} registered twice", day_def.day),
+            Err(pos) => self.days.insert(pos, day_def),
+        }
+    }
+
+    pub fn get(&self, day: u8) -> Option<&DayDef> {
+        self.days
+            .binary_search_by_key(&day, |d| d.day)
+            .ok()
+            .map(|pos| &self.days[pos])
+    }
+
+    pub fn days(&self) -> impl Iterator<Item = &DayDef> {
+        self.days.iter()
+    }
+
+    pub fn run_all(&self, options: &RunOption<'static>) -> Summary {
+        if options.get_nb_jobs() > 1 {
+            return self.run_all_parallel(options);
+        }
+        let mut summary = Summary::default();
+        for day_def in self.days() {
+            summary.extend(day_def.run(options));
+        }
+        summary
+    }
+
+    /// Runs every (day, part, dataset) on a pool of threads, the output of each run being buffered
+    /// and printed day by day once all runs are done so that logs are not interleaved
+    fn run_all_parallel(&self, options: &RunOption<'static>) -> Summary {
+        let days: Vec<(&DayDef, Arc<ExpectedAnswers>)> = self
+            .days()
+            .filter(|day_def| options.is_active(&day_def.day))
+            .map(|day_def| {
+                let expected = utils::load_expected_answers(&day_def.day, options);
+                (day_def, Arc::new(expected))
+            })
+            .collect();
+        let jobs: Vec<Job> = days
+            .iter()
+            .flat_map(|(day_def, expected)| {
+                day_def
+                    .jobs(options)
+                    .into_iter()
+                    .map(move |(part, data_set)| Job {
+                        day_def,
+                        expected,
+                        part,
+                        data_set,
+                    })
+            })
+            .collect();
+        let results = pool::run_parallel(&jobs, options.get_nb_jobs(), |job| job.run(options));
+
+        let mut summary = Summary::default();
+        let mut results = jobs.iter().zip(results).peekable();
+        for (day_def, _) in &days {
+            utils::print_day_header(&day_def.day, day_def.run_type(), options);
+            let mut reports = vec![];
+            while let Some((_, (report, output))) =
+                results.next_if(|(job, _)| job.day_def.day == day_def.day)
+            {
+                if !reports.is_empty() {
+                    options.print_line("");
+                }
+                output
+                    .iter()
+                    .for_each(|line| line.print(options.get_format()));
+                reports.push(report);
+            }
+            let elapsed = reports.iter().map(|r| r.elapsed).sum();
+            utils::print_day_footer_summed(&day_def.day, elapsed, options);
+            summary.extend(reports);
+        }
+        summary
+    }
+}
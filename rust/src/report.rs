@@ -0,0 +1,241 @@
+use std::time::Duration;
+
+use crate::{
+    answer::{Status, Value},
+    bench::Timings,
+    utils::{print_human, Dataset, OutputFormat, Part},
+};
+
+/// Outcome of one part of a run
+#[derive(Debug, Clone)]
+pub struct PartOutcome {
+    pub part: Part,
+    pub answer: Option<Value>,
+    pub expected: Option<Value>,
+    pub status: Status,
+}
+
+/// Outcome of one run of a day on a dataset, holding one outcome per part computed by the run
+#[derive(Debug, Clone)]
+pub struct RunReport {
+    pub day: u8,
+    /// Part of a per part run, `None` for a simult run
+    pub part: Option<Part>,
+    pub data_set: Dataset,
+    pub outcomes: Vec<PartOutcome>,
+    pub timings: Timings,
+    /// Wall-clock duration of the whole run, including warmup and input reading
+    pub elapsed: Duration,
+}
+
+fn json_string(value: &str) -> String {
+    let mut result = String::with_capacity(value.len() + 2);
+    result.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => result.push_str("\\\""),
+            '\\' => result.push_str("\\\\"),
+            '\n' => result.push_str("\\n"),
+            '\r' => result.push_str("\\r"),
+            '\t' => result.push_str("\\t"),
+            c if (c as u32) < 0x20 => result.push_str(&format!("\\u{:04x}", c as u32)),
+            c => result.push(c),
+        }
+    }
+    result.push('"');
+    result
+}
+
+fn json_value(value: &Option<Value>) -> String {
+    match value {
+        Some(Value::Int(v)) => v.to_string(),
+        Some(Value::Text(v)) => json_string(v),
+        None => String::from("null"),
+    }
+}
+
+impl RunReport {
+    pub fn status(&self) -> Status {
+        self.outcomes
+            .iter()
+            .fold(Status::Ok, |status, outcome| status.merge(outcome.status))
+    }
+
+    /// One JSON record per part computed by the run, times being the median ones in milliseconds
+    pub fn to_json_lines(&self) -> Vec<String> {
+        self.outcomes
+            .iter()
+            .map(|outcome| {
+                format!(
+                    "{{\"day\":{},\"part\":{},\"dataset\":{},\"answer\":{},\"expected\":{},\"status\":{},\"parse_ms\":{:.6},\"solve_ms\":{:.6},\"iterations\":{}}}",
+                    self.day,
+                    match outcome.part {
+                        Part::Part1 => 1,
+                        Part::Part2 => 2,
+                    },
+                    json_string(&self.data_set.name()),
+                    json_value(&outcome.answer),
+                    json_value(&outcome.expected),
+                    json_string(&outcome.status.to_string().to_lowercase()),
+                    self.timings.parse.median,
+                    self.timings.solve.median,
+                    self.timings.nb_iterations
+                )
+            })
+            .collect()
+    }
+}
+
+/// Width of a dataset column holding the names of the datasets of the completed runs
+pub fn data_set_width(reports: &[RunReport]) -> usize {
+    reports
+        .iter()
+        .filter(|r| r.status().is_completed())
+        .map(|r| r.data_set.to_string().len())
+        .fold("Dataset".len(), usize::max)
+}
+
+#[derive(Debug, Default)]
+pub struct Summary {
+    reports: Vec<RunReport>,
+}
+
+impl Summary {
+    pub fn extend(&mut self, reports: Vec<RunReport>) {
+        self.reports.extend(reports);
+    }
+
+    pub fn reports(&self) -> &[RunReport] {
+        &self.reports
+    }
+
+    /// Sum of the durations of all runs, which is above the overall wall-clock time for parallel runs
+    pub fn total_elapsed(&self) -> Duration {
+        self.reports.iter().map(|r| r.elapsed).sum()
+    }
+
+    pub fn has_failure(&self) -> bool {
+        self.reports.iter().any(|r| r.status().is_failure())
+    }
+
+    fn get_status(&self, day: u8, part: Part, data_set: Dataset) -> Option<Status> {
+        self.reports
+            .iter()
+            .filter(|r| r.day == day && r.data_set == data_set)
+            .flat_map(|r| r.outcomes.iter())
+            .find(|o| o.part == part)
+            .map(|o| o.status)
+    }
+
+    /// Prints one line per day and part, with the status for each dataset
+    pub fn print(&self, output: OutputFormat) {
+        let mut rows: Vec<(u8, Part)> = self
+            .reports
+            .iter()
+            .flat_map(|r| r.outcomes.iter().map(move |o| (r.day, o.part)))
+            .collect();
+        rows.sort_unstable_by_key(|(day, part)| (*day, *part as u8));
+        rows.dedup();
+        let mut data_sets: Vec<Dataset> = self.reports.iter().map(|r| r.data_set).collect();
+        data_sets.sort_unstable();
+        data_sets.dedup();
+
+        let widths: Vec<usize> = data_sets
+            .iter()
+            .map(|d| d.to_string().len().max(7))
+            .collect();
+
+        let format_status =
+            |status: Option<Status>| status.map_or(String::from("-"), |s| s.to_string());
+        print_human(output, "[ALL] Summary");
+        let header: Vec<String> = data_sets
+            .iter()
+            .zip(&widths)
+            .map(|(d, width)| format!("{:<width$}", d, width = width))
+            .collect();
+        print_human(
+            output,
+            format!("  Day | Part   | {}", header.join(" | ")).trim_end(),
+        );
+        let separators: String = widths
+            .iter()
+            .map(|width| format!("+{:-<width$}", "", width = width + 2))
+            .collect();
+        print_human(output, &format!("{:-<6}+{:-<8}{}", "", "", separators));
+        for (day, part) in rows {
+            let statuses: Vec<String> = data_sets
+                .iter()
+                .zip(&widths)
+                .map(|(data_set, width)| {
+                    format!(
+                        "{:<width$}",
+                        format_status(self.get_status(day, part, *data_set)),
+                        width = width
+                    )
+                })
+                .collect();
+            print_human(
+                output,
+                format!(
+                    "{:>5} | {:<6} | {}",
+                    day,
+                    format!("{:?}", part),
+                    statuses.join(" | ")
+                )
+                .trim_end(),
+            );
+        }
+        let count = |status: Status| {
+            self.reports
+                .iter()
+                .flat_map(|r| r.outcomes.iter())
+                .filter(|o| o.status == status)
+                .count()
+        };
+        print_human(
+            output,
+            &format!(
+                "[ALL] {} OK / {} KO / {} unknown / {} failed / {} timed out",
+                count(Status::Ok),
+                count(Status::Ko),
+                count(Status::Unknown),
+                count(Status::Failed),
+                count(Status::Timeout)
+            ),
+        );
+    }
+
+    /// Prints the timing statistics of each run, in milliseconds, runs without an answer being skipped
+    pub fn print_bench(&self, output: OutputFormat) {
+        let width = data_set_width(&self.reports);
+        print_human(output, "[ALL] Bench (ms)");
+        print_human(output, &format!(
+            "  Day | Part   | {:<width$} | Parse med. | Solve min  | Solve med. | Solve p95  | Solve max  | Std dev",
+            "Dataset",
+            width = width
+        ));
+        print_human(
+            output,
+            &format!(
+                "{:-<6}+{:-<8}+{:-<dataset$}+{:-<12}+{:-<12}+{:-<12}+{:-<12}+{:-<12}+{:-<10}",
+                "", "", "", "", "", "", "", "", "",
+                dataset = width + 2
+            ),
+        );
+        for report in self.reports.iter().filter(|r| r.status().is_completed()) {
+            print_human(output, &format!(
+                "{:>5} | {:<6} | {:<width$} | {:>10.3} | {:>10.3} | {:>10.3} | {:>10.3} | {:>10.3} | {:.3}",
+                report.day,
+                report.part.map_or(String::from("ALL"), |p| format!("{:?}", p)),
+                report.data_set.to_string(),
+                report.timings.parse.median,
+                report.timings.solve.min,
+                report.timings.solve.median,
+                report.timings.solve.p95,
+                report.timings.solve.max,
+                report.timings.solve.std_dev,
+                width = width
+            ));
+        }
+    }
+}
@@ -0,0 +1,297 @@
+use std::{collections::VecDeque, fmt, hash::Hash, ops::Add};
+
+use rustc_hash::{FxHashMap, FxHashSet};
+
+use crate::priority_queue::{Cost, IndexedPriorityQueue, Key, Order, PriorityQueue};
+
+/// Whether a search remembers how it reached each state, to give back the path to the goal
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tracking {
+    CostOnly,
+    Path,
+}
+
+impl Tracking {
+    /// Paths being only printed at debug level, they are tracked only then
+    pub fn when(is_debug: bool) -> Tracking {
+        if is_debug {
+            Tracking::Path
+        } else {
+            Tracking::CostOnly
+        }
+    }
+}
+
+/// Counters of a search, to compare explorations when debugging
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct SearchStats {
+    /// States popped from the queue
+    pub explored: usize,
+    /// States pushed to the queue, the start included
+    pub queued: usize,
+}
+
+impl fmt::Display for SearchStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "explored {} states, queued {}",
+            self.explored, self.queued
+        )
+    }
+}
+
+/// Cheapest path found to a goal
+#[derive(Debug)]
+pub struct Found<S, C> {
+    pub cost: C,
+    /// States from the start to the goal both included, when tracked
+    pub path: Option<Vec<S>>,
+}
+
+#[derive(Debug)]
+pub struct SearchResult<S, C> {
+    pub found: Option<Found<S, C>>,
+    pub stats: SearchStats,
+}
+
+impl<S, C> SearchResult<S, C> {
+    pub fn cost(&self) -> Option<C>
+    where
+        C: Copy,
+    {
+        self.found.as_ref().map(|found| found.cost)
+    }
+
+    pub fn path(&self) -> Option<&[S]> {
+        self.found.as_ref().and_then(|found| found.path.as_deref())
+    }
+}
+
+/// Predecessor of each state reached, by key, with the state itself
+struct Predecessors<S, K> {
+    states: FxHashMap<K, (Option<K>, S)>,
+}
+
+impl<S: Key<K> + Clone, K: Hash + Eq + Copy> Predecessors<S, K> {
+    fn new(tracking: Tracking, start: &S) -> Option<Predecessors<S, K>> {
+        (tracking == Tracking::Path).then(|| {
+            let mut states = FxHashMap::default();
+            states.insert(start.key(), (None, start.clone()));
+            Predecessors { states }
+        })
+    }
+
+    fn insert(&mut self, state: S, parent: K) {
+        self.states.insert(state.key(), (Some(parent), state));
+    }
+
+    /// States from the start to the one of the key
+    fn path_to(mut self, key: K) -> Vec<S> {
+        let mut path = vec![];
+        let mut current = Some(key);
+        while let Some((parent, state)) = current.and_then(|key| self.states.remove(&key)) {
+            path.push(state);
+            current = parent;
+        }
+        path.reverse();
+        path
+    }
+}
+
+/// Queued state with the cost spent to reach it, ordered by this cost plus its heuristic
+pub struct Node<S, C, K> {
+    state: S,
+    key: K,
+    spent: C,
+    priority: C,
+}
+
+impl<S, C: Copy, K> Cost<C> for Node<S, C, K> {
+    fn cost(&self) -> C {
+        self.priority
+    }
+}
+
+impl<S, C, K: Copy> Key<K> for Node<S, C, K> {
+    fn key(&self) -> K {
+        self.key
+    }
+}
+
+/// Queue of the nodes left to explore, keeping for each key the node of the best priority
+pub trait Frontier<T> {
+    /// Queues the node unless its key was already pushed with a priority as good, telling
+    /// whether it was queued
+    fn push(&mut self, node: T) -> bool;
+    fn pop(&mut self) -> Option<T>;
+}
+
+impl<C: Copy, K: Hash + Eq + Copy, T: Cost<C> + Key<K>, O: Order<C>> Frontier<T>
+    for PriorityQueue<C, K, T, O>
+{
+    fn push(&mut self, node: T) -> bool {
+        PriorityQueue::push(self, node)
+    }
+
+    fn pop(&mut self) -> Option<T> {
+        PriorityQueue::pop(self)
+    }
+}
+
+impl<C: Copy, K: Hash + Eq + Copy, T: Cost<C> + Key<K>, O: Order<C>> Frontier<T>
+    for IndexedPriorityQueue<C, K, T, O>
+{
+    fn push(&mut self, node: T) -> bool {
+        IndexedPriorityQueue::push(self, node)
+    }
+
+    fn pop(&mut self) -> Option<T> {
+        IndexedPriorityQueue::pop(self)
+    }
+}
+
+/// Cheapest path from the start to a goal state, `successors` giving the states reachable from a
+/// state with the cost of the step, and `heuristic` never overestimating the cost left to a goal
+pub fn a_star<S, K, C, N, I, G, H>(
+    tracking: Tracking,
+    start: S,
+    successors: N,
+    is_goal: G,
+    heuristic: H,
+) -> SearchResult<S, C>
+where
+    S: Key<K> + Clone,
+    K: Hash + Eq + Copy,
+    C: Copy + Ord + Default + Add<Output = C>,
+    N: FnMut(&S) -> I,
+    I: IntoIterator<Item = (S, C)>,
+    G: FnMut(&S) -> bool,
+    H: FnMut(&S) -> C,
+{
+    let queue: PriorityQueue<C, K, Node<S, C, K>> = PriorityQueue::new();
+    a_star_in(queue, tracking, start, successors, is_goal, heuristic)
+}
+
+/// A* exploring from the given queue, as an `IndexedPriorityQueue` popping the states of equal
+/// priorities in the order they were reached
+pub fn a_star_in<S, K, C, Q, N, I, G, H>(
+    mut queue: Q,
+    tracking: Tracking,
+    start: S,
+    mut successors: N,
+    mut is_goal: G,
+    mut heuristic: H,
+) -> SearchResult<S, C>
+where
+    S: Key<K> + Clone,
+    K: Hash + Eq + Copy,
+    C: Copy + Ord + Default + Add<Output = C>,
+    Q: Frontier<Node<S, C, K>>,
+    N: FnMut(&S) -> I,
+    I: IntoIterator<Item = (S, C)>,
+    G: FnMut(&S) -> bool,
+    H: FnMut(&S) -> C,
+{
+    let mut stats = SearchStats::default();
+    let mut predecessors = Predecessors::new(tracking, &start);
+    queue.push(Node {
+        key: start.key(),
+        spent: C::default(),
+        priority: heuristic(&start),
+        state: start,
+    });
+    stats.queued += 1;
+    while let Some(node) = queue.pop() {
+        stats.explored += 1;
+        if is_goal(&node.state) {
+            return SearchResult {
+                found: Some(Found {
+                    cost: node.spent,
+                    path: predecessors.map(|predecessors| predecessors.path_to(node.key)),
+                }),
+                stats,
+            };
+        }
+        for (state, step) in successors(&node.state) {
+            let tracked = predecessors.as_ref().map(|_| state.clone());
+            let next = Node {
+                key: state.key(),
+                spent: node.spent + step,
+                priority: node.spent + step + heuristic(&state),
+                state,
+            };
+            if queue.push(next) {
+                stats.queued += 1;
+                if let (Some(predecessors), Some(state)) = (predecessors.as_mut(), tracked) {
+                    predecessors.insert(state, node.key);
+                }
+            }
+        }
+    }
+    SearchResult { found: None, stats }
+}
+
+/// Cheapest path from the start to a goal state, `successors` giving the states reachable from a
+/// state with the cost of the step
+pub fn dijkstra<S, K, C, N, I, G>(
+    tracking: Tracking,
+    start: S,
+    successors: N,
+    is_goal: G,
+) -> SearchResult<S, C>
+where
+    S: Key<K> + Clone,
+    K: Hash + Eq + Copy,
+    C: Copy + Ord + Default + Add<Output = C>,
+    N: FnMut(&S) -> I,
+    I: IntoIterator<Item = (S, C)>,
+    G: FnMut(&S) -> bool,
+{
+    a_star(tracking, start, successors, is_goal, |_| C::default())
+}
+
+/// Shortest path in number of steps from the start to a goal state, `successors` giving the
+/// states reachable in one step from a state
+pub fn bfs<S, K, N, I, G>(
+    tracking: Tracking,
+    start: S,
+    mut successors: N,
+    mut is_goal: G,
+) -> SearchResult<S, usize>
+where
+    S: Key<K> + Clone,
+    K: Hash + Eq + Copy,
+    N: FnMut(&S) -> I,
+    I: IntoIterator<Item = S>,
+    G: FnMut(&S) -> bool,
+{
+    let mut stats = SearchStats::default();
+    let mut predecessors = Predecessors::new(tracking, &start);
+    let mut seen: FxHashSet<K> = FxHashSet::default();
+    seen.insert(start.key());
+    stats.queued += 1;
+    let mut queue = VecDeque::from([(start, 0)]);
+    while let Some((current, cost)) = queue.pop_front() {
+        stats.explored += 1;
+        if is_goal(&current) {
+            return SearchResult {
+                found: Some(Found {
+                    cost,
+                    path: predecessors.map(|predecessors| predecessors.path_to(current.key())),
+                }),
+                stats,
+            };
+        }
+        for state in successors(&current) {
+            if seen.insert(state.key()) {
+                stats.queued += 1;
+                if let Some(predecessors) = predecessors.as_mut() {
+                    predecessors.insert(state.clone(), current.key());
+                }
+                queue.push_back((state, cost + 1));
+            }
+        }
+    }
+    SearchResult { found: None, stats }
+}
@@ -0,0 +1,209 @@
+use std::ops::{Index, IndexMut};
+
+/// Node of a tree, valid only for the tree that created it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct NodeId(usize);
+
+/// Decision of a visit function, with the data to pass on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Flow<D> {
+    /// Go on with the children of the node, or its parent when walking to the root
+    Continue(D),
+    /// Stop there, pruning the children of the node or the rest of the walk to the root
+    Stop(D),
+}
+
+#[derive(Debug)]
+struct TreeNode<T> {
+    value: T,
+    parent: Option<NodeId>,
+    children: Vec<NodeId>,
+}
+
+/// Tree whose nodes are stored in a single vector, linked to their parent and children by id
+#[derive(Debug)]
+pub struct Tree<T> {
+    nodes: Vec<TreeNode<T>>,
+}
+
+impl<T> Tree<T> {
+    pub fn new(root: T) -> Tree<T> {
+        Tree {
+            nodes: vec![TreeNode {
+                value: root,
+                parent: None,
+                children: vec![],
+            }],
+        }
+    }
+
+    pub fn root(&self) -> NodeId {
+        NodeId(0)
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn add_child(&mut self, parent: NodeId, value: T) -> NodeId {
+        let id = NodeId(self.nodes.len());
+        self.nodes.push(TreeNode {
+            value,
+            parent: Some(parent),
+            children: vec![],
+        });
+        self.nodes[parent.0].children.push(id);
+        id
+    }
+
+    pub fn parent(&self, id: NodeId) -> Option<NodeId> {
+        self.nodes[id.0].parent
+    }
+
+    pub fn children(&self, id: NodeId) -> &[NodeId] {
+        &self.nodes[id.0].children
+    }
+
+    /// The node and its ancestors up to the root
+    pub fn ancestors(&self, id: NodeId) -> impl Iterator<Item = NodeId> + '_ {
+        std::iter::successors(Some(id), |id| self.parent(*id))
+    }
+
+    pub fn to_root(&self, id: NodeId) -> NodeId {
+        self.ancestors(id).last().unwrap_or(id)
+    }
+
+    /// Pre-order visit from the node, `fct` being given each node with the data returned for the
+    /// node visited before it and the ids of its ancestors from the starting node
+    pub fn visit<D, F>(&self, from: NodeId, data: D, mut fct: F) -> D
+    where
+        F: FnMut(NodeId, &T, D, &[NodeId]) -> Flow<D>,
+    {
+        let mut parents = vec![];
+        self.visit_from(from, data, &mut fct, &mut parents)
+    }
+
+    fn visit_from<D, F>(&self, id: NodeId, data: D, fct: &mut F, parents: &mut Vec<NodeId>) -> D
+    where
+        F: FnMut(NodeId, &T, D, &[NodeId]) -> Flow<D>,
+    {
+        match fct(id, &self[id], data, parents) {
+            Flow::Stop(data) => data,
+            Flow::Continue(mut data) => {
+                parents.push(id);
+                for child in self.children(id) {
+                    data = self.visit_from(*child, data, fct, parents);
+                }
+                parents.pop();
+                data
+            }
+        }
+    }
+
+    /// Walk from the node to the root, `fct` being given each node with the data returned for its
+    /// child
+    pub fn apply_to_root<D, F>(&mut self, from: NodeId, data: D, mut fct: F) -> D
+    where
+        F: FnMut(&mut T, D) -> Flow<D>,
+    {
+        let mut current = Some(from);
+        let mut data = data;
+        while let Some(id) = current {
+            match fct(&mut self[id], data) {
+                Flow::Stop(result) => return result,
+                Flow::Continue(result) => data = result,
+            }
+            current = self.parent(id);
+        }
+        data
+    }
+}
+
+impl<T> Index<NodeId> for Tree<T> {
+    type Output = T;
+
+    fn index(&self, id: NodeId) -> &T {
+        &self.nodes[id.0].value
+    }
+}
+
+impl<T> IndexMut<NodeId> for Tree<T> {
+    fn index_mut(&mut self, id: NodeId) -> &mut T {
+        &mut self.nodes[id.0].value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Tree of `root` with children `a` and `b`, `a` having the child `c`
+    fn sample() -> (Tree<char>, [NodeId; 4]) {
+        let mut tree = Tree::new('r');
+        let root = tree.root();
+        let a = tree.add_child(root, 'a');
+        let b = tree.add_child(root, 'b');
+        let c = tree.add_child(a, 'c');
+        (tree, [root, a, b, c])
+    }
+
+    #[test]
+    fn visits_in_pre_order_with_the_ancestors() {
+        let (tree, [root, a, _, _]) = sample();
+        let visited = tree.visit(root, vec![], |_, value, mut visited, parents| {
+            visited.push((*value, parents.len()));
+            Flow::Continue(visited)
+        });
+        assert_eq!(visited, [('r', 0), ('a', 1), ('c', 2), ('b', 1)]);
+        let from_a = tree.visit(a, String::new(), |_, value, text, parents| {
+            Flow::Continue(format!("{}{}{}", text, value, parents.len()))
+        });
+        assert_eq!(from_a, "a0c1");
+    }
+
+    #[test]
+    fn stopping_a_visit_prunes_the_children() {
+        let (tree, [root, a, _, _]) = sample();
+        let visited = tree.visit(root, String::new(), |id, value, text, _| {
+            let text = format!("{}{}", text, value);
+            if id == a {
+                Flow::Stop(text)
+            } else {
+                Flow::Continue(text)
+            }
+        });
+        assert_eq!(visited, "rab");
+    }
+
+    #[test]
+    fn applies_to_the_ancestors_until_stopped() {
+        let (mut tree, [root, a, b, c]) = sample();
+        let walked = tree.apply_to_root(c, 0, |value, count| {
+            *value = value.to_ascii_uppercase();
+            Flow::Continue(count + 1)
+        });
+        assert_eq!(walked, 3);
+        assert_eq!(
+            [tree[root], tree[a], tree[b], tree[c]],
+            ['R', 'A', 'b', 'C']
+        );
+        let walked = tree.apply_to_root(c, 0, |value, count| {
+            if *value == 'A' {
+                Flow::Stop(count)
+            } else {
+                Flow::Continue(count + 1)
+            }
+        });
+        assert_eq!(walked, 1);
+    }
+
+    #[test]
+    fn finds_the_root_and_counts_the_nodes() {
+        let (tree, [root, a, _, c]) = sample();
+        assert_eq!(tree.len(), 4);
+        assert_eq!(tree.to_root(c), root);
+        assert_eq!(tree.to_root(root), root);
+        assert_eq!(tree.ancestors(c).collect::<Vec<_>>(), [c, a, root]);
+        assert_eq!(tree.children(a), [c]);
+    }
+}
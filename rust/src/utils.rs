@@ -1,105 +1,132 @@
-use std::fmt::Display;
-use std::fs::File;
-use std::io::{self, BufRead, BufReader, Error, Lines};
+use std::fmt;
+use std::io::{self, Read, Write};
 use std::path::Path;
-use std::time::Instant;
+use std::any::Any;
+use std::cell::{Cell, RefCell};
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::{
+    answer::{Answer, ExpectedAnswers, Status, Value},
+    bench::Timings,
+    input::Puzzle,
+    parse::ParseError,
+    report::{PartOutcome, RunReport},
+};
 
 #[macro_export]
 macro_rules!
 log {
     ($level:tt,$ctxt:expr,$msg:expr) => (
-        $ctxt.$level(|| println!($msg))
+        $ctxt.$level(|| format!($msg))
     );
     ($level:tt, $ctxt:expr,$msg:expr, $($other:expr) ,*) => (
-        $ctxt.$level(|| println!($msg,$($other , )+))
+        $ctxt.$level(|| format!($msg,$($other , )+))
     );
 }
 
-#[macro_export]
-macro_rules! check_result {
-    ($ctxt:expr, [$res_p1:expr, $res_p2:expr ], [ $res_p1_test:expr,$res_p1_real:expr, $res_p2_test:expr, $res_p2_real:expr ] ) => {
-        if $ctxt.is_bench() {
-            return;
-        }
-        if $ctxt.has_part() {
-            panic!("Shoudn't be call in separate run context")
-        }
-        if ($ctxt.is_test()) {
-            $ctxt.check_both(($res_p1, $res_p2), ($res_p1_test, $res_p2_test))
-        } else {
-            $ctxt.check_both(($res_p1, $res_p2), ($res_p1_real, $res_p2_real))
-        }
-    };
-
-    ($ctxt:expr, $res:expr, [ $res1:expr, $res2:expr ] ) => {
-        if $ctxt.is_bench() {
-            return;
-        }
-        
-        if !$ctxt.has_part() {
-            panic!("Shoudn't be call in mono run context")
-        }
-        if ($ctxt.is_test()) {
-            $ctxt.check($res, $res1)
-        } else {
-            $ctxt.check($res, $res2)
+/// Input of the day in the data directory, else the one of the shared directory when given and
+/// only the latter exists
+fn get_applicable_filename_default(data_dir: &str, shared_dir: Option<&str>, day: &u8, data_set: &Dataset) -> String {
+    let filename = format!("{}/day_{}{}.txt", data_dir, day, data_set.file_suffix());
+    if let Some(shared_dir) = shared_dir {
+        let shared = format!("{}/day_{}{}.dat", shared_dir, day, data_set.file_suffix());
+        if !Path::new(&filename).exists() && Path::new(&shared).exists() {
+            return shared;
         }
-    };
-}
-
-fn read_lines_internal<P>(filename: P) -> Result<Lines<BufReader<File>>, Error>
-where
-    P: AsRef<Path>,
-{
-    let file = File::open(filename)?;
-    Ok(io::BufReader::new(file).lines())
-}
-
-fn get_applicable_filename_default(day: &u8, is_test: &Dataset) -> String {
-    return format!(
-        "./data/day_{}{}.txt",
-        day,
-        match is_test {
-            Dataset::Test => "_test",
-            _ => "",
-        }
-    );
+    }
+    filename
 }
 
-fn get_applicable_filename(day: &u8, part: Option<Part>, is_test: &Dataset) -> String {
+fn get_applicable_filename(data_dir: &str, shared_dir: Option<&str>, day: &u8, part: Option<Part>, data_set: &Dataset) -> String {
     return part
         .map(|p| {
             format!(
-                "./data/day_{}_{}{}.txt",
+                "{}/day_{}_{}{}.txt",
+                data_dir,
                 day,
                 match p {
                     Part::Part1 => 1,
                     Part::Part2 => 2,
                 },
-                match is_test {
-                    Dataset::Test => "_test",
-                    _ => "",
-                }
+                data_set.file_suffix()
             )
         })
         .filter(|name| std::path::Path::new(name.as_str()).exists())
-        .unwrap_or_else(|| get_applicable_filename_default(day, is_test));
+        .unwrap_or_else(|| get_applicable_filename_default(data_dir, shared_dir, day, data_set));
 }
 
-pub fn read_lines(
-    day: &u8,
-    part: Option<Part>,
-    is_test: &Dataset,
-) -> Option<Lines<BufReader<File>>> {
-    let f = read_lines_internal(get_applicable_filename(day, part, is_test));
+/// Names of the named test datasets of the day, found as `day_N_test_NAME.txt` or
+/// `day_N_P_test_NAME.txt` in the data directory
+fn discover_test_datasets(data_dir: &str, day: &u8) -> Vec<&'static str> {
+    let prefixes = [
+        format!("day_{}_test_", day),
+        format!("day_{}_1_test_", day),
+        format!("day_{}_2_test_", day),
+    ];
+    let mut names: Vec<&'static str> = std::fs::read_dir(data_dir)
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| entry.ok()?.file_name().into_string().ok())
+        .filter_map(|filename| {
+            let name = filename.strip_suffix(".txt")?;
+            prefixes
+                .iter()
+                .find_map(|prefix| name.strip_prefix(prefix.as_str()))
+                .filter(|name| !name.is_empty())
+                .map(intern)
+        })
+        .collect();
+    names.sort_unstable();
+    names.dedup();
+    names
+}
 
-    return match f {
-        Ok(lines) => Some(lines),
-        Err(_) => {
-            println!("No file found");
-            None
-        }
-    };
+fn read_file(filename: &str) -> Result<Vec<u8>, String> {
+    std::fs::read(filename).map_err(|error| format!("cannot read input {}: {}", filename, error))
+}
+
+/// Content of stdin, read on first use only so that every run of the input gets it
+fn read_stdin() -> Result<Vec<u8>, String> {
+    static STDIN_CONTENT: OnceLock<Result<Vec<u8>, String>> = OnceLock::new();
+    STDIN_CONTENT
+        .get_or_init(|| {
+            let mut content = vec![];
+            io::stdin()
+                .lock()
+                .read_to_end(&mut content)
+                .map(|_| content)
+                .map_err(|error| format!("cannot read input from stdin: {}", error))
+        })
+        .clone()
+}
+
+/// File of the input of a run: the explicit input for the `Input` dataset, else the file of the
+/// day, part and dataset in the data directory, `None` when reading stdin
+fn input_filename(options: &RunOption, day: &u8, part: Option<Part>, data_set: &Dataset) -> Option<String> {
+    match (data_set, options.get_input()) {
+        (Dataset::Input, Some(STDIN_INPUT)) => None,
+        (Dataset::Input, Some(filename)) => Some(filename.to_string()),
+        _ => Some(get_applicable_filename(
+            options.get_data_dir(),
+            options.get_shared_data_dir(),
+            day,
+            part,
+            data_set,
+        )),
+    }
+}
+
+/// Raw input of a run, read from its file or stdin
+pub fn read_input(options: &RunOption, day: &u8, part: Option<Part>, data_set: &Dataset) -> Result<Vec<u8>, String> {
+    match input_filename(options, day, part, data_set) {
+        Some(filename) => read_file(&filename),
+        None => read_stdin(),
+    }
 }
 
 #[derive(Eq, PartialEq, Clone, Copy)]
@@ -108,23 +135,118 @@ pub enum Mode {
     BENCH,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Part {
     Part1,
     Part2,
 }
 
-#[derive(Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[derive(Debug, Clone, Copy, Eq, PartialEq, PartialOrd, Ord)]
 pub enum LogLevel {
     ERROR = 0,
     INFO = 1,
     DEBUG = 2,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Dataset of a run, ordered as runs are: test ones, then real, then the explicit input
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum Dataset {
     Test,
+    /// Additional test input, read from `day_N_test_NAME.txt`
+    NamedTest(&'static str),
     Real,
+    /// Input given on the command line, as a file or stdin
+    Input,
+}
+
+/// Gives a name living for the whole program, the same name always leading to the same `&str`
+pub fn intern(name: &str) -> &'static str {
+    static NAMES: OnceLock<Mutex<Vec<&'static str>>> = OnceLock::new();
+    let mut names = NAMES.get_or_init(Default::default).lock().unwrap_or_else(|e| e.into_inner());
+    match names.iter().find(|n| **n == name) {
+        Some(interned) => interned,
+        None => {
+            let interned: &'static str = Box::leak(name.to_string().into_boxed_str());
+            names.push(interned);
+            interned
+        }
+    }
+}
+
+impl Dataset {
+    /// Name of the dataset in answers files and JSON records: `test`, `real`, `input` or `test_NAME`
+    pub fn name(&self) -> String {
+        match self {
+            Dataset::Test => String::from("test"),
+            Dataset::Real => String::from("real"),
+            Dataset::Input => String::from("input"),
+            Dataset::NamedTest(name) => format!("test_{}", name),
+        }
+    }
+
+    /// Parses a name given by [`Dataset::name`]
+    pub fn from_name(name: &str) -> Option<Dataset> {
+        match name {
+            "test" => Some(Dataset::Test),
+            "real" => Some(Dataset::Real),
+            "input" => Some(Dataset::Input),
+            _ => name
+                .strip_prefix("test_")
+                .filter(|name| !name.is_empty())
+                .map(|name| Dataset::NamedTest(intern(name))),
+        }
+    }
+
+    pub fn is_test(&self) -> bool {
+        matches!(self, Dataset::Test | Dataset::NamedTest(_))
+    }
+
+    fn file_suffix(&self) -> String {
+        match self {
+            Dataset::Real | Dataset::Input => String::new(),
+            _ => format!("_{}", self.name()),
+        }
+    }
+}
+
+impl fmt::Display for Dataset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Dataset::NamedTest(name) => f.pad(&format!("Test_{}", name)),
+            _ => f.pad(&format!("{:?}", self)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    Text,
+    JsonLines,
+}
+
+/// Prints a human readable line, on stderr when stdout is reserved to the JSON lines records
+pub fn print_human(format: OutputFormat, line: &str) {
+    match format {
+        OutputFormat::Text => println!("{}", line),
+        OutputFormat::JsonLines => eprintln!("{}", line),
+    }
+}
+
+/// Line produced by a run, kept in the run context when its output is buffered
+pub enum OutputLine {
+    Human(String),
+    Record(String),
+}
+
+impl OutputLine {
+    pub fn print(&self, format: OutputFormat) {
+        match self {
+            OutputLine::Human(line) => print_human(format, line),
+            OutputLine::Record(line) => {
+                writeln!(io::stdout().lock(), "{}", line).unwrap();
+            }
+        }
+    }
 }
 
 #[allow(dead_code)]
@@ -134,61 +256,90 @@ pub enum Active {
 }
 
 #[allow(dead_code)]
+#[derive(Clone)]
 pub struct Context {
     log_level: LogLevel,
     day: u8,
     data_set: Dataset,
     is_debug: bool,
-    is_bench: bool,
     part: Option<Part>,
+    format: OutputFormat,
+    /// Output of the run when it is not directly printed, as in parallel runs
+    buffer: Option<Arc<Mutex<Vec<OutputLine>>>>,
+    /// Set once the run is abandoned after a timeout, silencing whatever it still logs
+    cancelled: Arc<AtomicBool>,
 }
 
 impl Context {
-    fn new_part(day: &u8, options: &RunOption, part: Part, data_set: &Dataset) -> Context {
-        return Context::new(day, options, Some(part), data_set);
-    }
-
-    fn new_all(day: &u8, options: &RunOption, data_set: &Dataset) -> Context {
-        return Context::new(day, options, None, data_set);
-    }
-
     fn new(day: &u8, options: &RunOption, part: Option<Part>, data_set: &Dataset) -> Context {
         let log_level = options.get_log_level();
-        let is_debug = options.debug.unwrap_or(false);
-        let is_bench = options.mode.map(|m|m==Mode::BENCH).unwrap_or(false);
+        let is_debug = log_level == LogLevel::DEBUG;
         return Context {
             log_level: log_level,
             day: *day,
             data_set: *data_set,
             part: part,
             is_debug: is_debug,
-            is_bench: is_bench,
+            format: options.get_format(),
+            buffer: None,
+            cancelled: Arc::new(AtomicBool::new(false)),
         };
     }
 
-    fn log(&self, log_level: LogLevel, print_fct: impl Fn()) {
-        if log_level <= self.log_level {
-            match &self.part {
-                Some(p) => {
-                    print!("[Day {}/{:?}/{:?}]", self.day, p, self.data_set)
-                }
-                None => print!("[Day {}/ALL/{:?}]", self.day, self.data_set),
+    /// Context of a run whose output is kept until [`Context::take_output`] is called
+    pub fn new_buffered(day: &u8, options: &RunOption, part: Option<Part>, data_set: &Dataset) -> Context {
+        Context {
+            buffer: Some(Arc::new(Mutex::new(vec![]))),
+            ..Context::new(day, options, part, data_set)
+        }
+    }
+
+    pub fn take_output(&self) -> Vec<OutputLine> {
+        self.buffer
+            .as_ref()
+            .map(|buffer| std::mem::take(&mut *buffer.lock().unwrap_or_else(|e| e.into_inner())))
+            .unwrap_or_default()
+    }
+
+    fn emit(&self, line: OutputLine) {
+        if self.cancelled.load(Ordering::Relaxed) {
+            return;
+        }
+        match &self.buffer {
+            Some(buffer) => buffer.lock().unwrap_or_else(|e| e.into_inner()).push(line),
+            None => line.print(self.format),
+        }
+    }
+
+    fn emit_records(&self, report: &RunReport) {
+        if self.format == OutputFormat::JsonLines {
+            for record in report.to_json_lines() {
+                self.emit(OutputLine::Record(record));
             }
-            print_fct();
+        }
+    }
+
+    fn log(&self, log_level: LogLevel, format_fct: impl Fn() -> String) {
+        if log_level <= self.log_level {
+            let line = match &self.part {
+                Some(p) => format!("[Day {}/{:?}/{}]{}", self.day, p, self.data_set, format_fct()),
+                None => format!("[Day {}/ALL/{}]{}", self.day, self.data_set, format_fct()),
+            };
+            self.emit(OutputLine::Human(line));
         }
     }
 
     #[allow(dead_code)]
-    pub fn debug(&self, print_fct: impl Fn()) {
-        self.log(LogLevel::DEBUG, print_fct);
+    pub fn debug(&self, format_fct: impl Fn() -> String) {
+        self.log(LogLevel::DEBUG, format_fct);
     }
 
-    pub fn error(&self, print_fct: impl Fn()) {
-        self.log(LogLevel::ERROR, print_fct);
+    pub fn error(&self, format_fct: impl Fn() -> String) {
+        self.log(LogLevel::ERROR, format_fct);
     }
 
-    pub fn info(&self, print_fct: impl Fn()) {
-        self.log(LogLevel::INFO, print_fct);
+    pub fn info(&self, format_fct: impl Fn() -> String) {
+        self.log(LogLevel::INFO, format_fct);
     }
 
     #[allow(dead_code)]
@@ -204,123 +355,310 @@ impl Context {
         };
     }
 
-    pub fn is_bench(&self)->bool{
-        self.is_bench
+    /// Parts computed by a run in this context
+    fn parts(&self) -> Vec<Part> {
+        match self.part {
+            Some(part) => vec![part],
+            None => vec![Part::Part1, Part::Part2],
+        }
     }
 
-    pub fn has_part(&self) -> bool {
-        return match self.part {
-            Some(_) => true,
-            None => false,
-        };
+    pub fn is_test(&self) -> bool {
+        return self.data_set.is_test();
     }
+}
 
-    pub fn is_test(&self) -> bool {
-        return self.data_set == Dataset::Test;
+fn check_value(context: &Context, part: Part, value: &Value, expected: &ExpectedAnswers) -> PartOutcome {
+    let expected = expected.get(part, context.data_set).cloned();
+    let status = match &expected {
+        Some(expected_value) if expected_value == value => Status::Ok,
+        Some(_) => Status::Ko,
+        None => Status::Unknown,
+    };
+    PartOutcome {
+        part,
+        answer: Some(value.clone()),
+        expected,
+        status,
     }
+}
 
-    pub fn check<T: Eq + Display>(&self, val: T, expected: T) {
-        if val == expected {
-            log!(info, self, "Result OK {}", val);
-        } else {
-            log!(
-                error,
-                self,
-                "Result KO >>>{}<<<< instead of {})",
-                val,
-                expected
-            );
+fn check(context: &Context, answer: &Answer, expected: &ExpectedAnswers) -> Vec<PartOutcome> {
+    let outcomes = match (context.part, answer) {
+        (Some(part), Answer::Single(value)) => vec![check_value(context, part, value, expected)],
+        (None, Answer::Pair(value1, value2)) => vec![
+            check_value(context, Part::Part1, value1, expected),
+            check_value(context, Part::Part2, value2, expected),
+        ],
+        _ => {
+            log!(error, context, "Result KO {} doesn't match the run style", answer);
+            return context
+                .parts()
+                .into_iter()
+                .map(|part| PartOutcome {
+                    part,
+                    answer: None,
+                    expected: expected.get(part, context.data_set).cloned(),
+                    status: Status::Ko,
+                })
+                .collect();
         }
+    };
+    let format_expected = |outcome: &PartOutcome| {
+        outcome
+            .expected
+            .as_ref()
+            .map_or(String::from("?"), |v| v.to_string())
+    };
+    let expected_str = match outcomes.as_slice() {
+        [outcome] => format_expected(outcome),
+        _ => format!(
+            "({})",
+            outcomes.iter().map(format_expected).collect::<Vec<_>>().join(",")
+        ),
+    };
+    match outcomes
+        .iter()
+        .fold(Status::Ok, |status, outcome| status.merge(outcome.status))
+    {
+        Status::Ok => log!(info, context, "Result OK {}", answer),
+        Status::Unknown => log!(info, context, "Result unknown {} (expected {})", answer, expected_str),
+        _ => log!(
+            error,
+            context,
+            "Result KO >>>{}<<< instead of {}",
+            answer,
+            expected_str
+        ),
     }
+    outcomes
+}
+
+fn run_iterations(
+    context: &Context,
+    puzzle: &Puzzle,
+    expected: &ExpectedAnswers,
+    options: &RunOption,
+) -> Result<RunReport, RunFailure> {
+    log!(info, context, "Starting");
 
-    pub fn check_both<T: Eq + Display>(&self, val: (T, T), expected: (T, T)) {
-        if val.0 == expected.0 && val.1 == expected.1 {
-            log!(info, self, "Result OK ({},{})", val.0, val.1);
+    let start_run = Instant::now();
+    let nb_warmup = options.get_nb_warmup();
+    let nb_iterations = options.get_nb_iterations();
+    let mut parse_durations = Vec::with_capacity(nb_iterations as usize);
+    let mut solve_durations = Vec::with_capacity(nb_iterations as usize);
+    let mut answer = None;
+    if let Some(filename) = input_filename(options, &context.day, context.part, &context.data_set) {
+        if Path::new(&filename).starts_with(SHARED_DATA_DIR) {
+            log!(info, context, "Reading the shared input {}", filename);
         } else {
-            log!(
-                error,
-                self,
-                "Result KO (>>>{}<<<<,>>>{}<<<) instead of {},{})",
-                val.0,
-                val.1,
-                expected.0,
-                expected.1
-            );
-        }
-    }
-}
-
-pub fn run<F: Fn(&Context, &Vec<String>)>(
-    context: Context,
-    fct: &F,
-    mode: &Mode,
-) {
-    log!(info, &context, "Starting");
-
-    let start_read = Instant::now();
-    let lines = to_lines(&context.day, context.part, &context.data_set);
-    let read_duration = start_read.elapsed().as_secs_f32() * 1000.0;
-    let nb_max = if *mode == Mode::BENCH { 10 } else { 1 };
-    let start = Instant::now();
-    let mut count = 0;
-    while count < nb_max {
-        count += 1;
-        fct(&context, &lines);
-    }
-    let duration = start.elapsed().as_secs_f32() * 1000.0;
-
-    log!(
-        info,
-        context,
-        "Duration {:.2} ms (avg {:.2} for #{} iterations) and {:.2} ms for read",
-        duration,
-        duration / nb_max as f32,
-        count,
-        read_duration
-    );
+            log!(debug, context, "Reading the input {}", filename);
+        }
+    }
+    for count in 0..(nb_warmup + nb_iterations) {
+        let start_read = Instant::now();
+        let input = read_input(options, &context.day, context.part, &context.data_set).map_err(RunFailure::Input)?;
+        let read_duration = start_read.elapsed();
+        let to_failure = |error: ParseError| RunFailure::Parse(error.with_day(context.day));
+        let call = puzzle.call(context, &input).map_err(to_failure)?;
+        answer = Some(call.result.map_err(to_failure)?);
+        if count >= nb_warmup {
+            parse_durations.push(read_duration + call.prepare);
+            solve_durations.push(call.solve);
+        }
+    }
+    let timings = Timings::new(nb_warmup, &parse_durations, &solve_durations);
+    let outcomes = check(context, &answer.unwrap(), expected);
+
+    if options.is_bench() {
+        log!(
+            info,
+            context,
+            "Solve {} (#{} iterations after #{} warmup)",
+            timings.solve,
+            timings.nb_iterations,
+            timings.nb_warmup
+        );
+        log!(info, context, "Parse {}", timings.parse);
+    } else {
+        log!(
+            info,
+            context,
+            "Duration {:.2} ms and {:.2} ms for read",
+            timings.solve.mean,
+            timings.parse.mean
+        );
+    }
+    let report = RunReport {
+        day: context.day,
+        part: context.part,
+        data_set: context.data_set,
+        outcomes,
+        timings,
+        elapsed: start_run.elapsed(),
+    };
+    context.emit_records(&report);
+    Ok(report)
 }
 
-pub fn run_simult<F: Fn(&Context, &Vec<String>)>(
-    context: Context,
-    fct: &F,
-    mode: &Mode,
-) {
-    log!(info, context, "Starting");
+/// Why a run ended without an answer
+enum RunFailure {
+    Input(String),
+    Parse(ParseError),
+    Panic(String),
+    Timeout(Duration),
+}
 
-    let start_read = Instant::now();
-    let lines = to_lines(&context.day, None, &context.data_set);
-    let read_duration = start_read.elapsed().as_secs_f32() * 1000.0;
+thread_local! {
+    static IN_GUARDED_RUN: Cell<bool> = const { Cell::new(false) };
+    static GUARDED_PANIC: RefCell<Option<String>> = const { RefCell::new(None) };
+}
 
-    let nb_max = if *mode == Mode::BENCH { 10 } else { 1 };
-    let start = Instant::now();
-    let mut count = 0;
-    while count < nb_max {
-        count += 1;
-        fct(&context, &lines);
+/// Installs a panic hook keeping the message of a panic raised within a run, so that the run reports
+/// it instead of having it printed in the middle of the output. Other panics are printed as usual.
+pub fn install_panic_hook() {
+    let default_hook = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        if IN_GUARDED_RUN.with(Cell::get) {
+            let message = info.payload_as_str().unwrap_or("Box<dyn Any>");
+            let message = match info.location() {
+                Some(location) => format!("{} at {}", message, location),
+                None => message.to_string(),
+            };
+            GUARDED_PANIC.with(|panic| *panic.borrow_mut() = Some(message));
+        } else {
+            default_hook(info);
+        }
+    }));
+}
+
+fn payload_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        String::from("Box<dyn Any>")
     }
-    let duration = start.elapsed().as_secs_f32() * 1000.0;
-    log!(
-        info,
-        context,
-        "Duration {:.2} ms (avg {:.2} for #{} iterations) and {:.2} ms for read",
-        duration,
-        duration / nb_max as f32,
-        count,
-        read_duration
-    )
 }
 
-pub fn to_lines(day: &u8, part: Option<Part>, data_set: &Dataset) -> Vec<String> {
-    return read_lines(day, part, data_set)
-        .map(|lines| lines.map(|l| l.unwrap()).collect())
-        .unwrap_or(vec![]);
+fn catch_panic<R>(fct: impl FnOnce() -> R) -> Result<R, RunFailure> {
+    IN_GUARDED_RUN.with(|guarded| guarded.set(true));
+    let result = panic::catch_unwind(AssertUnwindSafe(fct));
+    IN_GUARDED_RUN.with(|guarded| guarded.set(false));
+    result.map_err(|payload| {
+        let message = GUARDED_PANIC
+            .with(|panic| panic.borrow_mut().take())
+            .unwrap_or_else(|| payload_message(payload.as_ref()));
+        RunFailure::Panic(message)
+    })
+}
+
+fn failed_report(context: &Context, failure: RunFailure, expected: &ExpectedAnswers, elapsed: Duration) -> RunReport {
+    let status = match failure {
+        RunFailure::Input(error) => {
+            log!(error, context, "Result FAILED, {}", error);
+            Status::Failed
+        }
+        RunFailure::Parse(error) => {
+            log!(error, context, "Result FAILED, invalid input: {}", error);
+            Status::Failed
+        }
+        RunFailure::Panic(message) => {
+            log!(error, context, "Result FAILED, panicked: {}", message);
+            Status::Failed
+        }
+        RunFailure::Timeout(timeout) => {
+            log!(error, context, "Result TIMEOUT, no answer after {:.2} s", timeout.as_secs_f32());
+            Status::Timeout
+        }
+    };
+    let report = RunReport {
+        day: context.day,
+        part: context.part,
+        data_set: context.data_set,
+        outcomes: context
+            .parts()
+            .into_iter()
+            .map(|part| PartOutcome {
+                part,
+                answer: None,
+                expected: expected.get(part, context.data_set).cloned(),
+                status,
+            })
+            .collect(),
+        timings: Timings::default(),
+        elapsed,
+    };
+    context.emit_records(&report);
+    report
+}
+
+/// Runs the puzzle in the given context, an invalid input or a panic being reported as a failed run.
+///
+/// With a timeout, the run is done on its own thread and abandoned once the timeout is elapsed: the
+/// thread can't be stopped and keeps on running in the background, but its output is dropped.
+pub fn run(context: &Context, puzzle: Puzzle, expected: &Arc<ExpectedAnswers>, options: &RunOption<'static>) -> RunReport {
+    let start = Instant::now();
+    let result = match options.get_timeout() {
+        None => catch_panic(|| run_iterations(context, &puzzle, expected, options)).and_then(|result| result),
+        Some(timeout) => {
+            let cancelled = Arc::new(AtomicBool::new(false));
+            let thread_context = Context {
+                cancelled: Arc::clone(&cancelled),
+                ..context.clone()
+            };
+            let (thread_expected, thread_options) = (Arc::clone(expected), *options);
+            let (sender, receiver) = mpsc::channel();
+            thread::spawn(move || {
+                let result = catch_panic(|| run_iterations(&thread_context, &puzzle, &thread_expected, &thread_options))
+                    .and_then(|result| result);
+                // The receiver is gone when the run timed out
+                let _ = sender.send(result);
+            });
+            match receiver.recv_timeout(timeout) {
+                Ok(result) => result,
+                Err(RecvTimeoutError::Timeout) => {
+                    cancelled.store(true, Ordering::Relaxed);
+                    Err(RunFailure::Timeout(timeout))
+                }
+                Err(RecvTimeoutError::Disconnected) => {
+                    Err(RunFailure::Panic(String::from("run thread ended without result")))
+                }
+            }
+        }
+    };
+    result.unwrap_or_else(|failure| failed_report(context, failure, expected, start.elapsed()))
 }
 
 pub type DaysRestriction<'a> = &'a Option<Vec<u8>>;
+
+/// Environment variable giving the data directory when none is given on the command line
+pub const DATA_DIR_ENV: &str = "AOC_DATA_DIR";
+pub const LOCAL_DATA_DIR: &str = "./data";
+/// Inputs shared with the other implementations of the repository, as `day_N.dat` files, read
+/// when the default data directory lacks an input
+pub const SHARED_DATA_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../data");
+/// Input name reading the input from stdin
+pub const STDIN_INPUT: &str = "-";
+pub const DEFAULT_BENCH_ITERATIONS: u32 = 10;
+pub const DEFAULT_BENCH_WARMUP: u32 = 1;
+
+#[derive(Clone, Copy)]
 pub struct RunOption<'a> {
     active: Option<bool>,
     mode: Option<Mode>,
     debug: Option<bool>,
+    log_level: Option<LogLevel>,
+    nb_iterations: Option<u32>,
+    nb_warmup: Option<u32>,
+    part_restriction: Option<Part>,
+    dataset_restriction: Option<Dataset>,
+    data_dir: Option<&'a str>,
+    format: Option<OutputFormat>,
+    nb_jobs: Option<usize>,
+    timeout: Option<Duration>,
+    input: Option<&'a str>,
     days_restriction: DaysRestriction<'a>,
 }
 
@@ -333,6 +671,16 @@ impl<'a> RunOption<'a> {
             debug: None,
             mode: None,
             active: None,
+            log_level: None,
+            nb_iterations: None,
+            nb_warmup: None,
+            part_restriction: None,
+            dataset_restriction: None,
+            data_dir: None,
+            format: None,
+            nb_jobs: None,
+            timeout: None,
+            input: None,
             days_restriction,
         }
     }
@@ -340,33 +688,136 @@ impl<'a> RunOption<'a> {
     #[allow(dead_code)]
     pub fn disabled() -> RunOption<'a> {
         RunOption {
-            debug: None,
-            mode: None,
             active: Some(false),
-            days_restriction: &None,
+            ..RunOption::new(&None)
         }
     }
     #[allow(dead_code)]
     pub fn debug(&self) -> RunOption<'a> {
         RunOption {
             debug: Some(true),
-            mode: self.mode,
-            active: self.active,
-            days_restriction: self.days_restriction,
+            ..*self
         }
     }
 
     #[allow(dead_code)]
     pub fn bench(&self) -> RunOption<'a> {
         RunOption {
-            active: self.active,
             mode: Some(Mode::BENCH),
-            debug: self.debug,
-            days_restriction: self.days_restriction,
+            ..*self
+        }
+    }
+
+    pub fn with_iterations(&self, nb_iterations: u32) -> RunOption<'a> {
+        RunOption {
+            nb_iterations: Some(nb_iterations),
+            ..*self
+        }
+    }
+
+    pub fn with_warmup(&self, nb_warmup: u32) -> RunOption<'a> {
+        RunOption {
+            nb_warmup: Some(nb_warmup),
+            ..*self
+        }
+    }
+
+    pub fn with_log_level(&self, log_level: LogLevel) -> RunOption<'a> {
+        RunOption {
+            log_level: Some(log_level),
+            ..*self
+        }
+    }
+
+    pub fn with_part(&self, part: Part) -> RunOption<'a> {
+        RunOption {
+            part_restriction: Some(part),
+            ..*self
+        }
+    }
+
+    pub fn with_dataset(&self, dataset: Dataset) -> RunOption<'a> {
+        RunOption {
+            dataset_restriction: Some(dataset),
+            ..*self
+        }
+    }
+
+    pub fn with_data_dir(&self, data_dir: &'a str) -> RunOption<'a> {
+        RunOption {
+            data_dir: Some(data_dir),
+            ..*self
+        }
+    }
+
+    pub fn with_format(&self, format: OutputFormat) -> RunOption<'a> {
+        RunOption {
+            format: Some(format),
+            ..*self
+        }
+    }
+
+    pub fn with_jobs(&self, nb_jobs: usize) -> RunOption<'a> {
+        RunOption {
+            nb_jobs: Some(nb_jobs),
+            ..*self
+        }
+    }
+
+    pub fn with_input(&self, input: &'a str) -> RunOption<'a> {
+        RunOption {
+            input: Some(input),
+            ..*self
+        }
+    }
+
+    /// Explicit input file, or [`STDIN_INPUT`], replacing the test and real datasets
+    pub fn get_input(&self) -> Option<&'a str> {
+        self.input
+    }
+
+    /// Datasets of the day to run: the explicit input when there is one, else the active ones among
+    /// test, the named tests found in the data directory and real
+    pub fn datasets(&self, day: &u8) -> Vec<Dataset> {
+        if self.input.is_some() {
+            return vec![Dataset::Input];
         }
+        let named_tests = discover_test_datasets(self.get_data_dir(), day)
+            .into_iter()
+            .map(Dataset::NamedTest);
+        std::iter::once(Dataset::Test)
+            .chain(named_tests)
+            .chain(std::iter::once(Dataset::Real))
+            .filter(|data_set| self.is_dataset_active(*data_set))
+            .collect()
     }
 
-    fn is_active(&self, day: &u8) -> bool {
+    pub fn with_timeout(&self, timeout: Duration) -> RunOption<'a> {
+        RunOption {
+            timeout: Some(timeout),
+            ..*self
+        }
+    }
+
+    /// Time given to each run to answer, runs having no limit by default
+    pub fn get_timeout(&self) -> Option<Duration> {
+        self.timeout
+    }
+
+    /// Number of runs executed concurrently, 1 meaning that runs are sequential with their output printed directly
+    pub fn get_nb_jobs(&self) -> usize {
+        self.nb_jobs.unwrap_or(1).max(1)
+    }
+
+    pub fn get_format(&self) -> OutputFormat {
+        self.format.unwrap_or(OutputFormat::Text)
+    }
+
+    pub fn print_line(&self, line: &str) {
+        print_human(self.get_format(), line);
+    }
+
+    pub fn is_active(&self, day: &u8) -> bool {
         if !self.active.unwrap_or(true) {
             return false;
         }
@@ -379,12 +830,54 @@ impl<'a> RunOption<'a> {
         return true;
     }
 
+    pub fn is_part_active(&self, part: Part) -> bool {
+        self.part_restriction.is_none_or(|p| p == part)
+    }
+
+    /// Whether the dataset is allowed, the `Test` restriction allowing every test dataset
+    pub fn is_dataset_active(&self, dataset: Dataset) -> bool {
+        self.dataset_restriction.is_none_or(|d| match d {
+            Dataset::Test => dataset.is_test(),
+            _ => d == dataset,
+        })
+    }
+
+    fn get_nb_iterations(&self) -> u32 {
+        match self.get_mode() {
+            Mode::BENCH => self.nb_iterations.unwrap_or(DEFAULT_BENCH_ITERATIONS).max(1),
+            Mode::STANDARD => 1,
+        }
+    }
+
+    fn get_nb_warmup(&self) -> u32 {
+        match self.get_mode() {
+            Mode::BENCH => self.nb_warmup.unwrap_or(DEFAULT_BENCH_WARMUP),
+            Mode::STANDARD => 0,
+        }
+    }
+
+    pub fn is_bench(&self) -> bool {
+        *self.get_mode() == Mode::BENCH
+    }
+
+    fn get_data_dir(&self) -> &'a str {
+        self.data_dir.unwrap_or_else(|| default_data_dir())
+    }
+
+    /// Directory of the inputs shared with the other implementations, only read when no data
+    /// directory is given
+    fn get_shared_data_dir(&self) -> Option<&'static str> {
+        (self.data_dir.is_none() && env_data_dir().is_none()).then_some(SHARED_DATA_DIR)
+    }
+
     fn get_mode(&self) -> &Mode {
         self.mode.as_ref().unwrap_or(&Mode::STANDARD)
     }
 
     fn get_log_level(&self) -> LogLevel {
-        if self.is_debug() {
+        if let Some(log_level) = self.log_level {
+            log_level
+        } else if self.is_debug() {
             LogLevel::DEBUG
         } else {
             LogLevel::INFO
@@ -396,71 +889,120 @@ impl<'a> RunOption<'a> {
     }
 }
 
-pub fn run_all<F: Fn(&Context, &Vec<String>)>(day: &u8, fct: &F, options: RunOption) {
-    if !options.is_active(day) {
-        return;
-    }
+/// Data directory used when none is given: the one of [`DATA_DIR_ENV`], else `./data` when it
+/// exists, else the one next to the sources so that the binary can run from any directory
+fn default_data_dir() -> &'static str {
+    static DEFAULT_DATA_DIR: OnceLock<String> = OnceLock::new();
+    DEFAULT_DATA_DIR.get_or_init(|| {
+        env_data_dir()
+            .or_else(|| Path::new(LOCAL_DATA_DIR).is_dir().then(|| LOCAL_DATA_DIR.to_string()))
+            .unwrap_or_else(|| concat!(env!("CARGO_MANIFEST_DIR"), "/data").to_string())
+    })
+}
 
-    let mode = options.get_mode();
-    
-    println!("");
-    println!("");
-    println!("[Day {}] run per part", day);
-    let start = Instant::now();
+fn env_data_dir() -> Option<String> {
+    std::env::var(DATA_DIR_ENV)
+        .ok()
+        .filter(|data_dir| !data_dir.is_empty())
+}
 
-    run(
-        Context::new_part(day, &options, Part::Part1, &Dataset::Test),
-        &fct,
-        mode,
-    );
-    println!("");
-    run(
-        Context::new_part(day, &options, Part::Part1, &Dataset::Real),
-        &fct,
-        mode,
-    );
-    println!("");
-    run(
-        Context::new_part(day, &options, Part::Part2, &Dataset::Test),
-        &fct,
-        mode,
-    );
-    println!("");
-    run(
-        Context::new_part(day, &options, Part::Part2, &Dataset::Real),
-        &fct,
-        mode,
-    );
-    let duration =  start.elapsed().as_secs_f32() * 1000.0;
-    println!("");
+pub fn print_day_header(day: &u8, run_type: &str, options: &RunOption) {
+    options.print_line("");
+    options.print_line("");
+    options.print_line(&format!("[Day {}] {}", day, run_type));
+}
 
-    println!(
+pub fn print_day_footer(day: &u8, duration: Duration, options: &RunOption) {
+    options.print_line("");
+    options.print_line(&format!(
         "[Day {}] done in {:.2} ms",
         day,
-        duration
-    );
+        duration.as_secs_f32() * 1000.0
+    ));
+}
+
+/// Footer of a day whose runs were spread over parallel jobs, the duration being the sum of the
+/// run times rather than the time taken by the day
+pub fn print_day_footer_summed(day: &u8, duration: Duration, options: &RunOption) {
+    options.print_line("");
+    options.print_line(&format!(
+        "[Day {}] done in {:.2} ms of summed run time",
+        day,
+        duration.as_secs_f32() * 1000.0
+    ));
+}
+
+pub fn load_expected_answers(day: &u8, options: &RunOption) -> ExpectedAnswers {
+    ExpectedAnswers::load(options.get_data_dir(), day).unwrap_or_else(|error| {
+        options.print_line(&format!("[Day {}] {}", day, error));
+        ExpectedAnswers::default()
+    })
+}
+
+/// Parts run by a puzzle called once per part
+pub const PER_PART_RUNS: &[Option<Part>] = &[Some(Part::Part1), Some(Part::Part2)];
+/// Part of a puzzle computing both parts in a single call
+pub const SIMULT_RUNS: &[Option<Part>] = &[None];
+
+/// Part (`None` for a simult run) and dataset of each run of the day allowed by the options, in
+/// the order they are run
+pub fn day_runs(day: &u8, parts: &[Option<Part>], options: &RunOption) -> Vec<(Option<Part>, Dataset)> {
+    parts
+        .iter()
+        .filter(|part| part.is_none_or(|p| options.is_part_active(p)))
+        .flat_map(|part| {
+            options
+                .datasets(day)
+                .into_iter()
+                .map(move |data_set| (*part, data_set))
+        })
+        .collect()
+}
+
+pub fn run_all(
+    day: &u8,
+    puzzle: Puzzle,
+    options: &RunOption<'static>,
+) -> Vec<RunReport> {
+    run_day(day, "run per part", PER_PART_RUNS, puzzle, options)
+}
+
+pub fn run_all_simult(
+    day: &u8,
+    puzzle: Puzzle,
+    options: &RunOption<'static>,
+) -> Vec<RunReport> {
+    run_day(day, "run global", SIMULT_RUNS, puzzle, options)
 }
 
-pub fn run_all_simult<F: Fn(&Context, &Vec<String>)>(day: &u8, fct: &F, options: RunOption) {
+fn run_day(
+    day: &u8,
+    run_type: &str,
+    parts: &[Option<Part>],
+    puzzle: Puzzle,
+    options: &RunOption<'static>,
+) -> Vec<RunReport> {
     if !options.is_active(day) {
-        return;
+        return vec![];
     }
-    let mode = options.get_mode();
-    println!("");
-    println!("");
-    println!("[Day {}] run global", day);
+
+    print_day_header(day, run_type, options);
     let start = Instant::now();
-    run_simult(Context::new_all(day, &options, &Dataset::Test), fct, mode);
-    println!("");
-    run_simult(Context::new_all(day, &options, &Dataset::Real), fct, mode);
-    let duration = start.elapsed().as_secs_f32() * 1000.0;
-    println!("");
-    
-    println!(
-        "[Day {}] done in {:.2} ms",
-        day,
-        duration
-    );
+    let expected = &Arc::new(load_expected_answers(day, options));
+    let mut reports = vec![];
+    for (part, data_set) in day_runs(day, parts, options) {
+        if !reports.is_empty() {
+            options.print_line("");
+        }
+        reports.push(run(
+            &Context::new(day, options, part, &data_set),
+            puzzle,
+            expected,
+            options,
+        ));
+    }
+    print_day_footer(day, start.elapsed(), options);
+    reports
 }
 
 #[allow(dead_code)]
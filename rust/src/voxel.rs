@@ -0,0 +1,322 @@
+use std::{
+    collections::VecDeque,
+    ops::{Add, Index, IndexMut, Sub},
+};
+
+/// 3D coordinate or vector
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Coord3 {
+    pub x: i32,
+    pub y: i32,
+    pub z: i32,
+}
+
+impl Coord3 {
+    /// Offsets to the cubes sharing a side
+    pub const SIDES: [Coord3; 6] = [
+        Coord3::new(-1, 0, 0),
+        Coord3::new(1, 0, 0),
+        Coord3::new(0, -1, 0),
+        Coord3::new(0, 1, 0),
+        Coord3::new(0, 0, -1),
+        Coord3::new(0, 0, 1),
+    ];
+
+    pub const fn new(x: i32, y: i32, z: i32) -> Coord3 {
+        Coord3 { x, y, z }
+    }
+
+    /// Smallest coordinates of both, axis by axis
+    pub fn min(self, other: Coord3) -> Coord3 {
+        Coord3::new(
+            self.x.min(other.x),
+            self.y.min(other.y),
+            self.z.min(other.z),
+        )
+    }
+
+    /// Largest coordinates of both, axis by axis
+    pub fn max(self, other: Coord3) -> Coord3 {
+        Coord3::new(
+            self.x.max(other.x),
+            self.y.max(other.y),
+            self.z.max(other.z),
+        )
+    }
+
+    pub fn neighbours6(self) -> impl Iterator<Item = Coord3> {
+        Coord3::SIDES.iter().map(move |offset| self + *offset)
+    }
+}
+
+impl Add for Coord3 {
+    type Output = Coord3;
+
+    fn add(self, other: Coord3) -> Coord3 {
+        Coord3::new(self.x + other.x, self.y + other.y, self.z + other.z)
+    }
+}
+
+impl Sub for Coord3 {
+    type Output = Coord3;
+
+    fn sub(self, other: Coord3) -> Coord3 {
+        Coord3::new(self.x - other.x, self.y - other.y, self.z - other.z)
+    }
+}
+
+/// Dense grid of the voxels from `min` to `max`, both included
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VoxelGrid<T> {
+    min: Coord3,
+    max: Coord3,
+    cells: Vec<T>,
+}
+
+/// Number of voxels from `min` to `max` on an axis, not positive when `min` is after `max`
+fn side(min: i32, max: i32) -> i64 {
+    i64::from(max) - i64::from(min) + 1
+}
+
+impl<T> VoxelGrid<T> {
+    /// Grid filled with `fill`, failing when `min` is after `max` or the voxels can't be held in
+    /// memory
+    pub fn new(min: Coord3, max: Coord3, fill: T) -> Result<VoxelGrid<T>, String>
+    where
+        T: Clone,
+    {
+        let len = [(min.x, max.x), (min.y, max.y), (min.z, max.z)]
+            .iter()
+            .try_fold(1usize, |len, (min, max)| {
+                usize::try_from(side(*min, *max))
+                    .ok()
+                    .filter(|side| *side > 0)
+                    .and_then(|side| len.checked_mul(side))
+            })
+            .filter(|len| {
+                len.checked_mul(std::mem::size_of::<T>())
+                    .is_some_and(|bytes| bytes <= isize::MAX as usize)
+            })
+            .ok_or_else(|| format!("cannot hold a grid from {:?} to {:?}", min, max))?;
+        Ok(VoxelGrid {
+            min,
+            max,
+            cells: vec![fill; len],
+        })
+    }
+
+    #[allow(dead_code)]
+    pub fn min(&self) -> Coord3 {
+        self.min
+    }
+
+    #[allow(dead_code)]
+    pub fn max(&self) -> Coord3 {
+        self.max
+    }
+
+    pub fn contains(&self, pos: Coord3) -> bool {
+        (self.min.x..=self.max.x).contains(&pos.x)
+            && (self.min.y..=self.max.y).contains(&pos.y)
+            && (self.min.z..=self.max.z).contains(&pos.z)
+    }
+
+    /// Index of the position in the cells, which must be inside of the grid
+    fn index_of(&self, pos: Coord3) -> usize {
+        let offset = |pos: i32, min: i32| (i64::from(pos) - i64::from(min)) as usize;
+        let width = side(self.min.x, self.max.x) as usize;
+        let height = side(self.min.y, self.max.y) as usize;
+        (offset(pos.z, self.min.z) * height + offset(pos.y, self.min.y)) * width
+            + offset(pos.x, self.min.x)
+    }
+
+    /// Voxel at the position, `None` outside of the grid
+    pub fn get(&self, pos: Coord3) -> Option<&T> {
+        self.contains(pos).then(|| &self.cells[self.index_of(pos)])
+    }
+
+    #[allow(dead_code)]
+    pub fn get_mut(&mut self, pos: Coord3) -> Option<&mut T> {
+        if self.contains(pos) {
+            let index = self.index_of(pos);
+            Some(&mut self.cells[index])
+        } else {
+            None
+        }
+    }
+
+    pub fn positions(&self) -> impl Iterator<Item = Coord3> {
+        let (min, max) = (self.min, self.max);
+        (min.z..=max.z).flat_map(move |z| {
+            (min.y..=max.y).flat_map(move |y| (min.x..=max.x).map(move |x| Coord3::new(x, y, z)))
+        })
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Coord3, &T)> {
+        self.positions().zip(self.cells.iter())
+    }
+
+    /// Neighbours of the position sharing a side with it inside the grid
+    pub fn neighbours6(&self, pos: Coord3) -> impl Iterator<Item = Coord3> + '_ {
+        pos.neighbours6()
+            .filter(|neighbour| self.contains(*neighbour))
+    }
+
+    /// Whether the position is on one of the faces of the grid
+    pub fn is_boundary(&self, pos: Coord3) -> bool {
+        self.contains(pos)
+            && (pos.x == self.min.x
+                || pos.x == self.max.x
+                || pos.y == self.min.y
+                || pos.y == self.max.y
+                || pos.z == self.min.z
+                || pos.z == self.max.z)
+    }
+
+    /// Voxels reachable from the starts going from side to side through the voxels accepted by
+    /// `can_enter`, starts included if accepted
+    pub fn flood_fill<F>(
+        &self,
+        starts: impl IntoIterator<Item = Coord3>,
+        can_enter: F,
+    ) -> VoxelGrid<bool>
+    where
+        F: Fn(Coord3, &T) -> bool,
+    {
+        let mut reached = VoxelGrid {
+            min: self.min,
+            max: self.max,
+            cells: vec![false; self.cells.len()],
+        };
+        let mut to_visit = VecDeque::new();
+        for start in starts {
+            if self.get(start).is_some_and(|voxel| can_enter(start, voxel)) && !reached[start] {
+                reached[start] = true;
+                to_visit.push_back(start);
+            }
+        }
+        while let Some(pos) = to_visit.pop_front() {
+            for neighbour in self.neighbours6(pos) {
+                if !reached[neighbour] && can_enter(neighbour, &self[neighbour]) {
+                    reached[neighbour] = true;
+                    to_visit.push_back(neighbour);
+                }
+            }
+        }
+        reached
+    }
+
+    /// Voxels reachable from outside of the grid, entering through its faces
+    pub fn flood_fill_outside<F>(&self, can_enter: F) -> VoxelGrid<bool>
+    where
+        F: Fn(Coord3, &T) -> bool,
+    {
+        let boundary: Vec<Coord3> = self
+            .positions()
+            .filter(|pos| self.is_boundary(*pos))
+            .collect();
+        self.flood_fill(boundary, can_enter)
+    }
+}
+
+impl<T> Index<Coord3> for VoxelGrid<T> {
+    type Output = T;
+
+    fn index(&self, pos: Coord3) -> &T {
+        assert!(self.contains(pos), "{:?} outside of the grid", pos);
+        &self.cells[self.index_of(pos)]
+    }
+}
+
+impl<T> IndexMut<Coord3> for VoxelGrid<T> {
+    fn index_mut(&mut self, pos: Coord3) -> &mut T {
+        assert!(self.contains(pos), "{:?} outside of the grid", pos);
+        let index = self.index_of(pos);
+        &mut self.cells[index]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Solid shell of the cube from 1 to 3 in a grid from 0 to 4, hollow at its center
+    fn hollow_cube() -> VoxelGrid<bool> {
+        let mut grid = VoxelGrid::new(Coord3::new(0, 0, 0), Coord3::new(4, 4, 4), false).unwrap();
+        for pos in grid.positions().collect::<Vec<_>>() {
+            let in_cube = [pos.x, pos.y, pos.z].iter().all(|v| (1..=3).contains(v));
+            let on_face = [pos.x, pos.y, pos.z].iter().any(|v| *v == 1 || *v == 3);
+            grid[pos] = in_cube && on_face;
+        }
+        grid
+    }
+
+    #[test]
+    fn outside_flood_fill_leaves_the_hollow_unreached() {
+        let grid = hollow_cube();
+        let outside = grid.flood_fill_outside(|_, solid| !*solid);
+        assert!(!outside[Coord3::new(2, 2, 2)]);
+        assert!(!outside[Coord3::new(1, 2, 3)]);
+        assert!(outside[Coord3::new(0, 0, 0)] && outside[Coord3::new(4, 2, 2)]);
+        assert_eq!(
+            outside.iter().filter(|(_, reached)| **reached).count(),
+            125 - 26 - 1
+        );
+    }
+
+    #[test]
+    fn outside_flood_fill_enters_through_a_hole() {
+        let mut grid = hollow_cube();
+        grid[Coord3::new(2, 2, 1)] = false;
+        let outside = grid.flood_fill_outside(|_, solid| !*solid);
+        assert!(outside[Coord3::new(2, 2, 1)] && outside[Coord3::new(2, 2, 2)]);
+    }
+
+    #[test]
+    fn flood_fill_starts_only_from_enterable_voxels_inside() {
+        let grid = hollow_cube();
+        let from_inside = grid.flood_fill([Coord3::new(2, 2, 2)], |_, solid| !*solid);
+        assert_eq!(
+            from_inside.iter().filter(|(_, reached)| **reached).count(),
+            1
+        );
+        let from_nowhere = grid
+            .flood_fill([Coord3::new(1, 1, 1), Coord3::new(-1, 0, 0)], |_, solid| {
+                !*solid
+            });
+        assert!(from_nowhere.iter().all(|(_, reached)| !*reached));
+    }
+
+    #[test]
+    fn indexes_from_a_negative_minimum() {
+        let mut grid = VoxelGrid::new(Coord3::new(-2, -1, 5), Coord3::new(0, 1, 6), 0).unwrap();
+        grid[Coord3::new(-2, 1, 6)] = 7;
+        assert_eq!(grid.get(Coord3::new(-2, 1, 6)), Some(&7));
+        assert_eq!(grid.get(Coord3::new(1, 1, 6)), None);
+        assert_eq!(grid.iter().map(|(_, value)| value).sum::<i32>(), 7);
+        assert!(grid.is_boundary(Coord3::new(-1, 0, 5)));
+        assert!(!grid.is_boundary(Coord3::new(-1, 0, 7)));
+    }
+
+    #[test]
+    #[should_panic(expected = "outside of the grid")]
+    fn index_outside_of_the_grid_panics() {
+        let grid = VoxelGrid::new(Coord3::new(0, 0, 0), Coord3::new(2, 2, 2), 0).unwrap();
+        let _ = grid[Coord3::new(3, 0, 0)];
+    }
+
+    #[test]
+    #[should_panic(expected = "outside of the grid")]
+    fn mutable_index_wrapping_to_another_row_panics() {
+        let mut grid = VoxelGrid::new(Coord3::new(0, 0, 0), Coord3::new(2, 2, 2), 0).unwrap();
+        grid[Coord3::new(-1, 1, 0)] = 1;
+    }
+
+    #[test]
+    fn refuses_grids_too_large_or_reversed() {
+        let far = Coord3::new(i32::MAX, i32::MAX, i32::MAX);
+        let lowest = Coord3::new(i32::MIN, i32::MIN, i32::MIN);
+        assert!(VoxelGrid::new(lowest, far, 0u8).is_err());
+        assert!(VoxelGrid::new(Coord3::new(0, 0, 0), Coord3::new(1, -1, 1), 0u8).is_err());
+    }
+}